@@ -1,5 +1,8 @@
 use std::ops::{Add, AddAssign};
 
+// North American Braille ASCII, indexed by the dot mask.
+const BRF_CHARACTERS: &[u8; 64] = b" A1B'K2L@CIF/MSP\"E3H9O6R^DJG>NTQ,*5<-U8V.%[$+X!&;:4\\0Z7(_?W]#Y)=";
+
 #[repr(u8)]
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub enum BraillePattern {
@@ -150,6 +153,14 @@ impl BraillePattern {
         }
     }
 
+    pub fn to_brf(&self) -> char {
+        if let BraillePattern::BrailleNewline = self {
+            '\n'
+        } else {
+            BRF_CHARACTERS[*self as usize] as char
+        }
+    }
+
     pub const fn has_lower_dots(&self) -> bool {
         (*self as u8 & 36) != 0
     }
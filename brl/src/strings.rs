@@ -8,7 +8,7 @@ use crate::BraillePattern;
 
 pub type InlineBrailleArray = [BraillePattern; 16];
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct BrailleString(SmallVec<InlineBrailleArray>);
 
 impl BrailleString {
@@ -27,6 +27,21 @@ impl BrailleString {
         self.0.iter().map(|c| c.to_unicode()).collect::<String>()
     }
 
+    pub fn to_dots(&self) -> String {
+        self.0
+            .iter()
+            .map(|c| match c.to_dots() {
+                d if d.is_empty() => "0".to_string(),
+                d => d,
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    pub fn to_brf(&self) -> String {
+        self.0.iter().map(|c| c.to_brf()).collect::<String>()
+    }
+
     pub const fn from_vec(value: SmallVec<InlineBrailleArray>) -> BrailleString {
         BrailleString(value)
     }
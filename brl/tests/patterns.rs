@@ -0,0 +1,60 @@
+use brl::{braille, BraillePattern, BrailleString};
+
+// All 64 cells as the numbers that from_dots accepts, e.g. 0b101 is 13.
+fn all_dots() -> impl Iterator<Item = (u8, usize)> {
+    (0u8..64).map(|mask| {
+        let dots = (1..=6)
+            .filter(|i| mask & (1 << (i - 1)) != 0)
+            .fold(0, |res, i| res * 10 + i);
+        (mask, dots)
+    })
+}
+
+#[test]
+fn dots_round_trip() {
+    for (mask, dots) in all_dots() {
+        let pattern = BraillePattern::from_dots(dots);
+        let expected = if dots == 0 {
+            String::new()
+        } else {
+            dots.to_string()
+        };
+        assert_eq!(pattern.to_dots(), expected);
+        assert_eq!(pattern.to_unicode() as u32, 0x2800 + mask as u32);
+        assert_eq!(pattern.has_lower_dots(), mask & 36 != 0, "dots {}", dots);
+    }
+}
+
+#[test]
+fn addition_merges_dots() {
+    let mut pattern = BraillePattern::from_dots(1) + BraillePattern::from_dots(36);
+    assert_eq!(pattern.to_dots(), "136");
+    pattern += BraillePattern::from_dots(1245);
+    assert_eq!(pattern.to_dots(), "123456");
+}
+
+#[test]
+#[should_panic(expected = "Invalid dot pattern.")]
+fn invalid_dots() {
+    BraillePattern::from_dots(17);
+}
+
+#[test]
+fn braille_macro() {
+    let s = braille![3456, 1, 0, 12];
+    assert_eq!(s.len(), 4);
+    assert_eq!(s.to_dots(), "3456 1 0 12");
+    assert_eq!(s.to_unicode(), "⠼⠁⠀⠃");
+    assert_eq!(s.to_brf(), "#A B");
+    assert!(braille![].is_empty());
+}
+
+#[test]
+fn string_concatenation() {
+    let mut s = BrailleString::new();
+    s += BraillePattern::from_dots(46);
+    s += &braille![1, 2];
+    s.push_str(&braille![123]);
+    assert_eq!(s.to_dots(), "46 1 2 123");
+    assert_eq!(s.to_string(), s.to_unicode());
+}
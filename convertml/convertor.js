@@ -1,10 +1,39 @@
 let temml = require('temml')
 let process = require('process')
+let readline = require('readline')
 
-try {
-console.log(temml.renderToString(process.argv.slice(2).join(' '), {throwOnError: true, displayMode: true}))
+// mhchem adds \ce and \pu for chemistry, its script registers them with the global temml.
+global.temml = temml
+require('temml/contrib/mhchem/mhchem.js')
+
+const options = {
+    throwOnError: true,
+    displayMode: true,
+    // temml spells these out letter by letter, which can't be told apart from a product of variables.
+    macros: {
+        '\\pmod': '\\quad(\\operatorname{mod}\\,#1)',
+        '\\mod': '\\quad\\operatorname{mod}\\,#1',
+    },
+}
+
+if (process.argv[2] === '--batch') {
+    // Every input line is a JSON string with a formula, every output line is a JSON object with either mathml or error.
+    let rl = readline.createInterface({input: process.stdin, crlfDelay: Infinity})
+    rl.on('line', (line) => {
+        try {
+            console.log(JSON.stringify({mathml: temml.renderToString(JSON.parse(line), options)}))
+        }
+        catch (e) {
+            console.log(JSON.stringify({error: `${e}`}))
+        }
+    })
+}
+else {
+    try {
+    console.log(temml.renderToString(process.argv.slice(2).join(' '), options))
+    }
+    catch (e) {
+        console.log(`Error: ${e}`)
+        process.exit(1)
+    }
 }
-catch (e) {
-    console.log(`Error: ${e}`)
-    process.exit(1)
-}
\ No newline at end of file
@@ -0,0 +1,106 @@
+use std::{
+    fs,
+    io::{self, Read, Write},
+    path::Path,
+};
+
+use serde_json::{json, Value};
+
+use crate::{format_output, tex, InputFormat, OutputFormat};
+
+struct Item {
+    id: Value,
+    tex: Result<String, String>,
+}
+
+fn read_input(input: &Path) -> io::Result<String> {
+    if input.as_os_str() == "-" {
+        let mut res = String::new();
+        io::stdin().read_to_string(&mut res)?;
+        Ok(res)
+    } else {
+        fs::read_to_string(input)
+    }
+}
+
+fn parse_items(input: &str, jsonl: bool) -> Vec<Item> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !jsonl || !line.trim().is_empty())
+        .map(|(i, line)| {
+            if !jsonl {
+                return Item {
+                    id: json!(i + 1),
+                    tex: Ok(line.to_string()),
+                };
+            }
+            let value: Value = match serde_json::from_str(line) {
+                Ok(v) => v,
+                Err(e) => {
+                    return Item {
+                        id: json!(i + 1),
+                        tex: Err(format!("invalid JSON: {}", e)),
+                    }
+                }
+            };
+            let id = value.get("id").cloned().unwrap_or_else(|| json!(i + 1));
+            let tex = match value.get("tex").and_then(Value::as_str) {
+                Some(tex) => Ok(tex.to_string()),
+                None => Err("missing tex field".to_string()),
+            };
+            Item { id, tex }
+        })
+        .collect()
+}
+
+// Returns false if any of the formulas failed to render.
+pub fn run(
+    input: &Path,
+    input_format: InputFormat,
+    jsonl: bool,
+    format: OutputFormat,
+) -> io::Result<bool> {
+    let items = parse_items(&read_input(input)?, jsonl);
+    let formulas: Vec<String> = items
+        .iter()
+        .filter_map(|i| i.tex.as_ref().ok())
+        .filter(|tex| !tex.trim().is_empty())
+        .cloned()
+        .collect();
+    let mut converted = match input_format {
+        InputFormat::Tex => tex::convert_batch(&formulas)?,
+        InputFormat::Asciimath => formulas
+            .iter()
+            .map(|f| Ok(rbmath::asciimath::to_mathml(f)))
+            .collect(),
+        InputFormat::Mathml => formulas.iter().cloned().map(Ok).collect(),
+    }
+    .into_iter();
+    let mut stdout = io::stdout().lock();
+    let mut success = true;
+    for item in items {
+        let res = match item.tex {
+            Ok(tex) if tex.trim().is_empty() => Ok(String::new()),
+            Ok(_) => converted.next().unwrap().and_then(|ml| {
+                rbmath::render_with_options(&ml, tex::RENDER_OPTIONS)
+                    .map(|res| format_output(&res, format))
+                    .map_err(|e| e.to_string())
+            }),
+            Err(e) => Err(e),
+        };
+        success &= res.is_ok();
+        match (jsonl, res) {
+            (true, Ok(braille)) => {
+                writeln!(stdout, "{}", json!({"id": item.id, "braille": braille}))?
+            }
+            (true, Err(e)) => writeln!(stdout, "{}", json!({"id": item.id, "error": e}))?,
+            (false, Ok(braille)) => writeln!(stdout, "{}", braille)?,
+            (false, Err(e)) => {
+                eprintln!("Line {}: {}", item.id, e);
+                writeln!(stdout)?;
+            }
+        }
+    }
+    Ok(success)
+}
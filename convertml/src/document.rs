@@ -0,0 +1,534 @@
+use std::{fs, io, path::PathBuf};
+
+use brl::{BraillePattern, BrailleString};
+use clap::Args;
+use rbmath::literary;
+
+use crate::{format_output, tex, OutputFormat};
+
+const MATH_ENVIRONMENTS: &[&str] = &[
+    "equation",
+    "equation*",
+    "displaymath",
+    "align",
+    "align*",
+    "gather",
+    "gather*",
+    "multline",
+    "multline*",
+    "eqnarray",
+    "eqnarray*",
+];
+const LIST_ENVIRONMENTS: &[&str] = &["enumerate", "itemize"];
+const HEADING_COMMANDS: &[&str] = &[
+    "title",
+    "section",
+    "section*",
+    "subsection",
+    "subsection*",
+    "subsubsection",
+    "subsubsection*",
+    "paragraph",
+];
+const PARAGRAPH_INDENT: usize = 2;
+
+#[derive(Args)]
+pub struct DocumentArgs {
+    /// LaTeX document to transcribe.
+    input: PathBuf,
+    /// Output file, stdout if not specified.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Number of cells in a line.
+    #[arg(long, default_value_t = 30)]
+    width: usize,
+    /// Number of lines on a page.
+    #[arg(long, default_value_t = 25)]
+    height: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Event {
+    Text(String),
+    Math(String),
+    Display(String),
+    Paragraph,
+    ListStart(bool),
+    ListEnd,
+    Item(Option<String>),
+}
+
+fn body(source: &str) -> &str {
+    let start = source
+        .find("\\begin{document}")
+        .map_or(0, |i| i + "\\begin{document}".len());
+    let end = source.find("\\end{document}").unwrap_or(source.len());
+    &source[start..end.max(start)]
+}
+
+// Returns the contents of a group starting with the given opening bracket and the rest of the string.
+fn group(s: &str, open: char, close: char) -> Option<(&str, &str)> {
+    let mut depth = 0;
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            c if c == open => depth += 1,
+            c if c == close => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((&s[open.len_utf8()..i], &s[i + close.len_utf8()..]));
+                }
+            }
+            _ => {}
+        }
+        if depth == 0 {
+            return None;
+        }
+    }
+    None
+}
+
+fn command_name(s: &str) -> &str {
+    let len = s
+        .char_indices()
+        .find(|(_, c)| !c.is_ascii_alphabetic())
+        .map_or(s.len(), |(i, _)| i);
+    let name = &s[..len];
+    if s[len..].starts_with('*') {
+        &s[..len + 1]
+    } else {
+        name
+    }
+}
+
+fn strip_labels(tex: &str) -> String {
+    let mut res = String::new();
+    let mut rest = tex;
+    while let Some(i) = rest.find("\\label") {
+        res += &rest[..i];
+        rest = &rest[i + "\\label".len()..];
+        if let Some((_, after)) = group(rest.trim_start(), '{', '}') {
+            rest = after;
+        }
+    }
+    res += rest;
+    res.replace("\\nonumber", "").replace("\\notag", "")
+}
+
+fn clean_text(text: &str) -> String {
+    text.replace("---", "—")
+        .replace("--", "–")
+        .replace("``", "«")
+        .replace("''", "»")
+        .replace("<<", "«")
+        .replace(">>", "»")
+}
+
+struct Scanner {
+    events: Vec<Event>,
+    text: String,
+}
+
+impl Scanner {
+    fn flush(&mut self) {
+        if !self.text.is_empty() {
+            let text = clean_text(&std::mem::take(&mut self.text));
+            self.events.push(Event::Text(text));
+        }
+    }
+
+    fn push(&mut self, event: Event) {
+        self.flush();
+        self.events.push(event);
+    }
+
+    fn display(&mut self, tex: &str, multiline: bool) {
+        let tex = strip_labels(tex);
+        if multiline {
+            for row in tex.split("\\\\") {
+                let row = row.replace('&', "");
+                if !row.trim().is_empty() {
+                    self.push(Event::Display(row.trim().to_string()));
+                }
+            }
+        } else {
+            self.push(Event::Display(tex.trim().to_string()));
+        }
+    }
+
+    // Unterminated math delimiters are dropped.
+    fn scan(&mut self, mut s: &str) {
+        while let Some(c) = s.chars().next() {
+            let closing = |s: &str, open: &str, close: &str| {
+                s[open.len()..].find(close).map(|i| {
+                    (
+                        s[open.len()..open.len() + i].to_string(),
+                        open.len() + i + close.len(),
+                    )
+                })
+            };
+            let (event, len) = if s.starts_with("$$") {
+                match closing(s, "$$", "$$") {
+                    Some((tex, len)) => (Some(Event::Display(tex)), len),
+                    None => (None, 2),
+                }
+            } else if c == '$' {
+                match closing(s, "$", "$") {
+                    Some((tex, len)) => (Some(Event::Math(tex)), len),
+                    None => (None, 1),
+                }
+            } else if s.starts_with("\\(") {
+                match closing(s, "\\(", "\\)") {
+                    Some((tex, len)) => (Some(Event::Math(tex)), len),
+                    None => (None, 2),
+                }
+            } else if s.starts_with("\\[") {
+                match closing(s, "\\[", "\\]") {
+                    Some((tex, len)) => (Some(Event::Display(tex)), len),
+                    None => (None, 2),
+                }
+            } else if c == '\\' {
+                s = self.command(&s[1..]);
+                continue;
+            } else if c == '%' {
+                (None, s.find('\n').unwrap_or(s.len()))
+            } else if c == '\n'
+                && s[1..]
+                    .trim_start_matches([' ', '\t', '\r'])
+                    .starts_with('\n')
+            {
+                self.push(Event::Paragraph);
+                (None, s.len() - s[1..].trim_start().len())
+            } else {
+                match c {
+                    '{' | '}' => {}
+                    '~' => self.text.push(' '),
+                    c => self.text.push(c),
+                }
+                (None, c.len_utf8())
+            };
+            match event {
+                Some(Event::Display(tex)) => self.display(&tex, false),
+                Some(event) => self.push(event),
+                None => {}
+            }
+            s = &s[len..];
+        }
+        self.flush();
+    }
+
+    // Handles a command without the leading backslash, returns the rest of the input.
+    fn command<'a>(&mut self, s: &'a str) -> &'a str {
+        let name = command_name(s);
+        let rest = &s[name.len()..];
+        match name {
+            "" => {
+                let Some(c) = s.chars().next() else {
+                    return s;
+                };
+                match c {
+                    '\\' | ',' | ' ' | ';' | ':' => self.text.push(' '),
+                    c => self.text.push(c),
+                }
+                &s[c.len_utf8()..]
+            }
+            "begin" | "end" => {
+                let Some((env, after)) = group(rest, '{', '}') else {
+                    return rest;
+                };
+                if name == "begin" && MATH_ENVIRONMENTS.contains(&env) {
+                    let end = format!("\\end{{{}}}", env);
+                    let (tex, after) = after.split_at(after.find(&end).unwrap_or(after.len()));
+                    let multiline = !env.starts_with("equation") && !env.starts_with("displaymath");
+                    self.display(tex, multiline);
+                    return after.get(end.len()..).unwrap_or("");
+                }
+                if LIST_ENVIRONMENTS.contains(&env) {
+                    self.push(if name == "begin" {
+                        Event::ListStart(env == "enumerate")
+                    } else {
+                        Event::ListEnd
+                    });
+                }
+                after
+            }
+            "item" => {
+                let trimmed = rest.trim_start();
+                if trimmed.starts_with('[') {
+                    if let Some((label, after)) = group(trimmed, '[', ']') {
+                        self.push(Event::Item(Some(clean_text(label))));
+                        return after;
+                    }
+                }
+                self.push(Event::Item(None));
+                rest
+            }
+            "par" => {
+                self.push(Event::Paragraph);
+                rest
+            }
+            "ldots" | "dots" => {
+                self.text.push('…');
+                rest
+            }
+            name if HEADING_COMMANDS.contains(&name) => {
+                let Some((heading, after)) = group(rest.trim_start(), '{', '}') else {
+                    return rest;
+                };
+                self.push(Event::Paragraph);
+                self.scan(heading);
+                self.push(Event::Paragraph);
+                after
+            }
+            // Formatting commands are dropped, their arguments are kept as text.
+            _ => rest,
+        }
+    }
+}
+
+fn scan(source: &str) -> Vec<Event> {
+    let mut scanner = Scanner {
+        events: Vec::new(),
+        text: String::new(),
+    };
+    scanner.scan(body(source));
+    scanner.events
+}
+
+struct Layout {
+    width: usize,
+    lines: Vec<BrailleString>,
+    line: BrailleString,
+    word: BrailleString,
+    indent: usize,
+}
+
+impl Layout {
+    fn break_line(&mut self) {
+        self.flush_word();
+        if !self.line.is_empty() {
+            self.lines.push(std::mem::take(&mut self.line));
+        }
+    }
+
+    fn flush_word(&mut self) {
+        if self.word.is_empty() {
+            return;
+        }
+        let word = std::mem::take(&mut self.word);
+        if !self.line.is_empty() && self.line.len() + 1 + word.len() > self.width {
+            self.lines.push(std::mem::take(&mut self.line));
+        }
+        if self.line.is_empty() {
+            for _ in 0..self.indent {
+                self.line += BraillePattern::BrailleEmpty;
+            }
+            self.indent = 0;
+        } else {
+            self.line += BraillePattern::BrailleEmpty;
+        }
+        // Words longer than a line are split wherever the line ends.
+        for &c in word.iter() {
+            if self.line.len() == self.width {
+                self.lines.push(std::mem::take(&mut self.line));
+            }
+            self.line += c;
+        }
+    }
+
+    fn push_text(&mut self, text: &str) {
+        for (i, part) in text.split(char::is_whitespace).enumerate() {
+            if i > 0 {
+                self.flush_word();
+            }
+            if !part.is_empty() {
+                self.word += &literary::render_text(part);
+            }
+        }
+    }
+
+    fn new_line(&mut self, indent: usize) {
+        self.break_line();
+        self.indent = indent;
+    }
+}
+
+fn render_math(tex: &str, mathml: Option<Result<String, String>>) -> BrailleString {
+    match mathml.unwrap_or_else(|| Err("missing conversion".to_string())) {
+        Ok(ml) => match rbmath::render_with_options(&ml, tex::RENDER_OPTIONS) {
+            Ok(res) => return res,
+            Err(e) => log::error!("Failed to render {}: {}", tex, e),
+        },
+        Err(e) => log::error!("Failed to convert {}: {}", tex, e),
+    }
+    BrailleString::from(BraillePattern::Braille123456)
+}
+
+fn layout(events: &[Event], width: usize) -> io::Result<Vec<BrailleString>> {
+    let formulas: Vec<String> = events
+        .iter()
+        .filter_map(|e| match e {
+            Event::Math(tex) | Event::Display(tex) => Some(tex.clone()),
+            _ => None,
+        })
+        .collect();
+    let mut converted = tex::convert_batch(&formulas)?.into_iter();
+    let mut l = Layout {
+        width,
+        lines: Vec::new(),
+        line: BrailleString::new(),
+        word: BrailleString::new(),
+        indent: PARAGRAPH_INDENT,
+    };
+    // Item counters of the enclosing lists, None for unnumbered ones.
+    let mut lists: Vec<Option<usize>> = Vec::new();
+    for e in events {
+        match e {
+            Event::Text(text) => l.push_text(text),
+            Event::Math(tex) => l.word += &render_math(tex, converted.next()),
+            Event::Display(tex) => {
+                l.new_line(PARAGRAPH_INDENT);
+                l.word += &render_math(tex, converted.next());
+                l.new_line(0);
+            }
+            Event::Paragraph => l.new_line(PARAGRAPH_INDENT),
+            Event::ListStart(numbered) => lists.push(numbered.then_some(0)),
+            Event::ListEnd => {
+                lists.pop();
+                l.new_line(PARAGRAPH_INDENT);
+            }
+            Event::Item(label) => {
+                l.new_line(0);
+                let label = match (label, lists.last_mut()) {
+                    (Some(label), _) => label.clone(),
+                    (None, Some(Some(n))) => {
+                        *n += 1;
+                        format!("{})", n)
+                    }
+                    (None, _) => "–".to_string(),
+                };
+                l.push_text(&label);
+                l.flush_word();
+            }
+        }
+    }
+    l.break_line();
+    Ok(l.lines)
+}
+
+pub fn run(args: &DocumentArgs, format: OutputFormat) -> io::Result<()> {
+    let source = fs::read_to_string(&args.input)?;
+    let lines = layout(&scan(&source), args.width.max(1))?;
+    let pages: Vec<String> = lines
+        .chunks(args.height.max(1))
+        .map(|page| {
+            page.iter()
+                .map(|line| format_output(line, format))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect();
+    let res = pages.join("\n\x0c") + "\n";
+    match &args.output {
+        Some(path) => fs::write(path, res),
+        None => {
+            print!("{}", res);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Event {
+        Event::Text(s.to_string())
+    }
+
+    #[test]
+    fn math_delimiters() {
+        assert_eq!(
+            scan("$a$ и \\(b\\), $$c$$ \\[d\\]"),
+            [
+                Event::Math("a".to_string()),
+                text(" и "),
+                Event::Math("b".to_string()),
+                text(", "),
+                Event::Display("c".to_string()),
+                text(" "),
+                Event::Display("d".to_string()),
+            ]
+        );
+        // An unterminated delimiter is dropped.
+        assert_eq!(scan("$a"), [text("a")]);
+    }
+
+    #[test]
+    fn escaped_dollar() {
+        assert_eq!(
+            scan("цена \\$5 и $x$"),
+            [text("цена $5 и "), Event::Math("x".to_string())]
+        );
+    }
+
+    #[test]
+    fn document_body() {
+        assert_eq!(
+            scan("\\documentclass{article}\n\\begin{document}Текст\\end{document}"),
+            [text("Текст")]
+        );
+    }
+
+    #[test]
+    fn math_environments() {
+        assert_eq!(
+            scan("\\begin{equation}x=1\\label{eq:x}\\end{equation}"),
+            [Event::Display("x=1".to_string())]
+        );
+        assert_eq!(
+            scan("\\begin{align*}a&=b\\\\c&=d\\nonumber\\end{align*}"),
+            [
+                Event::Display("a=b".to_string()),
+                Event::Display("c=d".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn lists_and_headings() {
+        assert_eq!(
+            scan("\\section{Итоги}\\begin{enumerate}\\item один\\item[б)] два\\end{enumerate}"),
+            [
+                Event::Paragraph,
+                text("Итоги"),
+                Event::Paragraph,
+                Event::ListStart(true),
+                Event::Item(None),
+                text(" один"),
+                Event::Item(Some("б)".to_string())),
+                text(" два"),
+                Event::ListEnd,
+            ]
+        );
+    }
+
+    #[test]
+    fn line_wrapping() {
+        let lines = layout(&[text("один два три")], 8).unwrap();
+        // The first line starts with the paragraph indent.
+        assert_eq!(
+            lines.iter().map(|l| l.len()).collect::<Vec<_>>(),
+            [2 + 4, 3 + 1 + 3]
+        );
+        assert_eq!(lines[0].to_dots(), "0 0 135 145 24 1345");
+    }
+
+    #[test]
+    fn long_words_are_split() {
+        let lines = layout(&[text("абвгдежзий")], 4).unwrap();
+        assert_eq!(lines.iter().map(|l| l.len()).collect::<Vec<_>>(), [4, 4, 4]);
+    }
+}
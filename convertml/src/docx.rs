@@ -0,0 +1,32 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::PathBuf,
+};
+
+use clap::Args;
+use zip::ZipArchive;
+
+use crate::{format_output, OutputFormat};
+
+#[derive(Args)]
+pub struct DocxArgs {
+    /// Word document to extract the equations from.
+    input: PathBuf,
+}
+
+// Prints every equation of the document on a separate line, in document order.
+pub fn run(args: &DocxArgs, format: OutputFormat) -> io::Result<()> {
+    let mut archive = ZipArchive::new(File::open(&args.input)?)?;
+    let mut document = String::new();
+    archive
+        .by_name("word/document.xml")?
+        .read_to_string(&mut document)?;
+    let equations = rbmath::omml::render_document(&document)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    log::info!("Found {} equations", equations.len());
+    for res in &equations {
+        println!("{}", format_output(res, format));
+    }
+    Ok(())
+}
@@ -0,0 +1,280 @@
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use clap::Args;
+use rbmath::xhtml::{self, MathMode, Transcription};
+use roxmltree::Document;
+use zip::{write::FileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::tex;
+
+const CONTENT_EXTENSIONS: &[&str] = &["xhtml", "html", "htm"];
+
+#[derive(Args)]
+pub struct HtmlArgs {
+    /// XHTML or EPUB document to transcribe.
+    input: PathBuf,
+    /// Output file. XHTML is written to stdout by default, EPUB next to the input.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Keep the formulas and add the braille as their aria-braillelabel attribute instead of replacing them.
+    #[arg(long)]
+    annotate: bool,
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| extensions.iter().any(|x| x.eq_ignore_ascii_case(e)))
+}
+
+fn transcribe(name: &str, source: &str, mode: MathMode) -> io::Result<Transcription> {
+    let res = xhtml::transcribe(source, mode, tex::RENDER_OPTIONS)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", name, e)))?;
+    if res.failed > 0 {
+        log::warn!("{}: {} formulas failed to render", name, res.failed);
+    }
+    log::info!("{}: rendered {} formulas", name, res.rendered);
+    Ok(res)
+}
+
+// The position right after the closing quote of an attribute value.
+fn attribute_end(source: &str, position: usize) -> usize {
+    let value = position + source[position..].find(['"', '\'']).unwrap();
+    let quote = &source[value..value + 1];
+    value + 1 + source[value + 1..].find(quote).unwrap() + 1
+}
+
+// Documents whose formulas were all replaced no longer contain MathML, which the manifest declares.
+fn remove_mathml_property(
+    opf: &str,
+    base: &str,
+    without_math: &HashSet<String>,
+) -> Result<String, roxmltree::Error> {
+    let document = Document::parse(opf)?;
+    let mut edits = Vec::new();
+    for item in document.descendants().filter(|n| n.has_tag_name("item")) {
+        let Some(href) = item.attribute("href") else {
+            continue;
+        };
+        if !without_math.contains(&format!("{}{}", base, href)) {
+            continue;
+        }
+        let Some(attribute) = item.attributes().find(|a| a.name() == "properties") else {
+            continue;
+        };
+        let properties: Vec<&str> = attribute
+            .value()
+            .split_whitespace()
+            .filter(|&p| p != "mathml")
+            .collect();
+        let start = attribute.position();
+        let end = attribute_end(opf, start);
+        if properties.is_empty() {
+            let start = opf[..start].trim_end().len();
+            edits.push((start..end, String::new()));
+        } else {
+            edits.push((
+                start..end,
+                format!("properties=\"{}\"", properties.join(" ")),
+            ));
+        }
+    }
+    let mut res = opf.to_string();
+    for (range, replacement) in edits.into_iter().rev() {
+        res.replace_range(range, &replacement);
+    }
+    Ok(res)
+}
+
+fn write_epub(output: &Path, entries: Vec<(String, Option<Vec<u8>>)>) -> io::Result<()> {
+    let mut writer = ZipWriter::new(File::create(output)?);
+    for (name, content) in entries {
+        let Some(content) = content else {
+            writer.add_directory(name, FileOptions::default())?;
+            continue;
+        };
+        // The mimetype entry must stay uncompressed for readers to recognise the EPUB.
+        let method = if name == "mimetype" {
+            CompressionMethod::Stored
+        } else {
+            CompressionMethod::Deflated
+        };
+        writer.start_file(name, FileOptions::default().compression_method(method))?;
+        writer.write_all(&content)?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+// Documents that can't be transcribed are copied unchanged. The EPUB is written to a temporary file
+// first, so that a failure doesn't leave a broken book in place of the output.
+fn transcribe_epub(input: &Path, output: &Path, mode: MathMode) -> io::Result<()> {
+    let mut archive = ZipArchive::new(File::open(input)?)?;
+    let mut entries = Vec::with_capacity(archive.len());
+    let mut without_math = HashSet::new();
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        let name = file.name().to_string();
+        if file.is_dir() {
+            entries.push((name, None));
+            continue;
+        }
+        let mut content = Vec::new();
+        file.read_to_end(&mut content)?;
+        if has_extension(Path::new(&name), CONTENT_EXTENSIONS) {
+            let res = std::str::from_utf8(&content)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", name, e)))
+                .and_then(|source| transcribe(&name, source, mode));
+            match res {
+                Ok(res) => {
+                    if mode == MathMode::Replace && res.failed == 0 {
+                        without_math.insert(name.clone());
+                    }
+                    content = res.document.into_bytes();
+                }
+                Err(e) => log::error!("{}, copied unchanged", e),
+            }
+        }
+        entries.push((name, Some(content)));
+    }
+    // The package document is transcribed last, when it is known which documents still have formulas.
+    for (name, content) in &mut entries {
+        let Some(content) = content else {
+            continue;
+        };
+        if !has_extension(Path::new(name), &["opf"]) {
+            continue;
+        }
+        let base = name.rfind('/').map_or("", |i| &name[..i + 1]);
+        let res = match std::str::from_utf8(content) {
+            Ok(opf) => remove_mathml_property(opf, base, &without_math).map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        match res {
+            Ok(opf) => *content = opf.into_bytes(),
+            Err(e) => log::error!("{}: {}, copied unchanged", name, e),
+        }
+    }
+    let mut temporary = output.as_os_str().to_owned();
+    temporary.push(".part");
+    let temporary = PathBuf::from(temporary);
+    if let Err(e) = write_epub(&temporary, entries) {
+        let _ = fs::remove_file(&temporary);
+        return Err(e);
+    }
+    fs::rename(&temporary, output)
+}
+
+pub fn run(args: &HtmlArgs) -> io::Result<()> {
+    let mode = if args.annotate {
+        MathMode::Annotate
+    } else {
+        MathMode::Replace
+    };
+    if has_extension(&args.input, &["epub"]) {
+        let output = args
+            .output
+            .clone()
+            .unwrap_or_else(|| args.input.with_extension("braille.epub"));
+        return transcribe_epub(&args.input, &output, mode);
+    }
+    let name = args.input.display().to_string();
+    let res = transcribe(&name, &fs::read_to_string(&args.input)?, mode)?.document;
+    match &args.output {
+        Some(path) => fs::write(path, res),
+        None => {
+            print!("{}", res);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPF: &str = "<package xmlns=\"http://www.idpf.org/2007/opf\"><manifest>\
+        <item id=\"a\" href=\"a.xhtml\" media-type=\"application/xhtml+xml\" properties=\"mathml\"/>\
+        <item id=\"b\" href=\"b.xhtml\" media-type=\"application/xhtml+xml\" properties='scripted mathml'/>\
+        <item id=\"c\" href=\"c.xhtml\" media-type=\"application/xhtml+xml\" properties=\"mathml\"/>\
+        </manifest></package>";
+    const PAGE: &str = "<html xmlns=\"http://www.w3.org/1999/xhtml\"><body>\
+        <math xmlns=\"http://www.w3.org/1998/Math/MathML\"><mi>x</mi></math></body></html>";
+    const BROKEN_PAGE: &str = "<html><body><math><mi>x</mi></math>";
+
+    fn write_book(name: &str) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("convertml-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let entries = [
+            ("mimetype", "application/epub+zip"),
+            ("OEBPS/content.opf", OPF),
+            ("OEBPS/a.xhtml", PAGE),
+            ("OEBPS/b.xhtml", PAGE),
+            ("OEBPS/c.xhtml", BROKEN_PAGE),
+        ];
+        let input = dir.join("book.epub");
+        write_epub(
+            &input,
+            entries
+                .iter()
+                .map(|(name, content)| (name.to_string(), Some(content.as_bytes().to_vec())))
+                .collect(),
+        )
+        .unwrap();
+        (dir, input)
+    }
+
+    fn read_entry(path: &Path, name: &str) -> String {
+        let mut archive = ZipArchive::new(File::open(path).unwrap()).unwrap();
+        let mut res = String::new();
+        archive
+            .by_name(name)
+            .unwrap()
+            .read_to_string(&mut res)
+            .unwrap();
+        res
+    }
+
+    #[test]
+    fn replace_in_epub() {
+        let (dir, input) = write_book("replace");
+        let output = dir.join("book.braille.epub");
+        transcribe_epub(&input, &output, MathMode::Replace).unwrap();
+        let mut archive = ZipArchive::new(File::open(&output).unwrap()).unwrap();
+        let mimetype = archive.by_index(0).unwrap();
+        assert_eq!(mimetype.name(), "mimetype");
+        assert_eq!(mimetype.compression(), CompressionMethod::Stored);
+        drop(mimetype);
+        assert!(read_entry(&output, "OEBPS/a.xhtml").contains("<span class=\"braille\">"));
+        // A document that can't be parsed is copied unchanged and keeps its formulas.
+        assert_eq!(read_entry(&output, "OEBPS/c.xhtml"), BROKEN_PAGE);
+        let opf = read_entry(&output, "OEBPS/content.opf");
+        assert!(
+            opf.contains("<item id=\"a\" href=\"a.xhtml\" media-type=\"application/xhtml+xml\"/>")
+        );
+        assert!(opf.contains(
+            "href=\"b.xhtml\" media-type=\"application/xhtml+xml\" properties=\"scripted\"/>"
+        ));
+        assert!(opf.contains(
+            "href=\"c.xhtml\" media-type=\"application/xhtml+xml\" properties=\"mathml\"/>"
+        ));
+        assert!(!dir.join("book.braille.epub.part").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn annotate_in_epub() {
+        let (dir, input) = write_book("annotate");
+        let output = dir.join("book.braille.epub");
+        transcribe_epub(&input, &output, MathMode::Annotate).unwrap();
+        assert!(read_entry(&output, "OEBPS/a.xhtml").contains("aria-braillelabel="));
+        // The formulas are kept, so is the property.
+        assert_eq!(read_entry(&output, "OEBPS/content.opf"), OPF);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
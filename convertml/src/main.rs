@@ -1,38 +1,210 @@
-use clap::Parser;
-use std::{ffi::OsString, io::Write, os::windows::process::CommandExt, process::Command};
-use std::env;
+mod batch;
+mod document;
+mod docx;
+mod html;
+mod repl;
+mod serve;
+mod tex;
+
+use brl::BrailleString;
+use clap::{Parser, Subcommand, ValueEnum};
+use rbmath::{
+    speech::{Language, SpeechOptions, Verbosity},
+    Chemistry,
+};
+use std::fs;
+use std::{ffi::OsString, io::Write, os::windows::process::CommandExt, path::PathBuf, process::Command};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Unicode,
+    Dots,
+    Brf,
+    Latex,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum InputFormat {
+    Tex,
+    Asciimath,
+    Mathml,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum SpeechLanguage {
+    Ru,
+    En,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum SpeechVerbosity {
+    Brief,
+    Medium,
+    Verbose,
+}
+
+#[derive(Subcommand)]
+enum CliCommand {
+    /// Transcribe a LaTeX document with embedded formulas.
+    Document(document::DocumentArgs),
+    /// Extract and render the equations of a Word document.
+    Docx(docx::DocxArgs),
+    /// Transcribe the MathML formulas of an XHTML or EPUB document.
+    Html(html::HtmlArgs),
+    /// Render formulas interactively.
+    Repl(repl::ReplArgs),
+    /// Serve an HTTP JSON API for rendering formulas.
+    Serve(serve::ServeArgs),
+}
 
 #[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true)]
 struct CliArgs {
-    #[arg(short, long)]
+    #[command(subcommand)]
+    command: Option<CliCommand>,
+    /// Shorthand for --format latex.
+    #[arg(short, long, global = true)]
     latex: bool,
-    expr: OsString,
+    /// Output code. Defaults to unicode, the REPL shows all codes.
+    #[arg(short, long, global = true, value_enum)]
+    format: Option<OutputFormat>,
+    /// Notation of the input formulas.
+    #[arg(id = "input_format", short, long = "input", value_name = "FORMAT", value_enum, default_value_t = InputFormat::Tex)]
+    input_format: InputFormat,
+    /// Import math, sign and word opcodes from a liblouis table before rendering.
+    #[arg(short, long, global = true)]
+    table: Option<PathBuf>,
+    /// Print the built-in symbol tables in liblouis format and exit.
+    #[arg(long)]
+    export_table: bool,
+    /// Render every line of a file (- for stdin) as a separate formula.
+    #[arg(short, long)]
+    batch: Option<PathBuf>,
+    /// Read batch lines as JSON objects with id and tex fields and write results as JSON lines.
+    #[arg(long, requires = "batch")]
+    jsonl: bool,
+    /// Also print the spoken form of the formula.
+    #[arg(short, long)]
+    speech: bool,
+    #[arg(long, value_enum, default_value_t = SpeechLanguage::Ru, requires = "speech")]
+    language: SpeechLanguage,
+    #[arg(long, value_enum, default_value_t = SpeechVerbosity::Medium, requires = "speech")]
+    verbosity: SpeechVerbosity,
+    #[arg(required_unless_present_any = ["export_table", "batch"])]
+    expr: Option<OsString>,
+}
+
+fn format_output(res: &BrailleString, format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Unicode => res.to_unicode(),
+        OutputFormat::Dots => res.to_dots(),
+        OutputFormat::Brf => res.to_brf(),
+        OutputFormat::Latex => res
+            .iter()
+            .map(|c| format!("\\braillebox{{{}}} ", c.to_dots()))
+            .collect::<String>(),
+    }
+}
+
+fn speech_options(language: SpeechLanguage, verbosity: SpeechVerbosity) -> SpeechOptions {
+    SpeechOptions {
+        language: match language {
+            SpeechLanguage::Ru => Language::Russian,
+            SpeechLanguage::En => Language::English,
+        },
+        verbosity: match verbosity {
+            SpeechVerbosity::Brief => Verbosity::Brief,
+            SpeechVerbosity::Medium => Verbosity::Medium,
+            SpeechVerbosity::Verbose => Verbosity::Verbose,
+        },
+        chemistry: Chemistry::Auto,
+    }
 }
 
 fn main() {
     simple_logger::init().unwrap();
-    env::set_current_dir(env::current_exe().unwrap().parent().unwrap()).unwrap();
     let args = CliArgs::parse();
-    let output = Command::new("node")
-        .raw_arg("convertor.js")
-        .raw_arg(&args.expr)
-        .output()
-        .unwrap();
-    if output.status.success() {
-        let mathml = String::from_utf8(output.stdout).unwrap();
-        let res = rbmath::render(&mathml);
-        if args.latex {
-            println!(
-                "{}",
-                res.iter()
-                    .map(|c| format!("\\braillebox{{{}}} ", c.to_dots()))
-                    .collect::<String>()
-            );
-        } else {
-            println!("{}", res);
-        }
+    let chosen_format = if args.latex {
+        Some(OutputFormat::Latex)
     } else {
-        std::io::stderr().write_all(&output.stdout).unwrap();
-        std::io::stderr().write_all(&output.stderr).unwrap();
+        args.format
+    };
+    let format = chosen_format.unwrap_or(OutputFormat::Unicode);
+    if args.export_table {
+        print!("{}", rbmath::liblouis::export_table());
+        return;
+    }
+    if let Some(table) = &args.table {
+        let table = match fs::read_to_string(table) {
+            Ok(table) => table,
+            Err(e) => {
+                eprintln!("Cannot read {}: {}", table.display(), e);
+                std::process::exit(1);
+            }
+        };
+        match rbmath::liblouis::import_table(&table) {
+            Ok(count) => log::info!("Imported {} symbols", count),
+            Err(e) => {
+                eprintln!("Invalid liblouis table: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(command) = &args.command {
+        let res = match command {
+            CliCommand::Document(document_args) => document::run(document_args, format),
+            CliCommand::Docx(docx_args) => docx::run(docx_args, format),
+            CliCommand::Html(html_args) => html::run(html_args),
+            CliCommand::Repl(repl_args) => repl::run(repl_args, chosen_format),
+            CliCommand::Serve(serve_args) => serve::run(serve_args),
+        };
+        if let Err(e) = res {
+            eprintln!("Transcription failed: {}", e);
+            std::process::exit(2);
+        }
+        return;
+    }
+    if let Some(input) = &args.batch {
+        match batch::run(input, args.input_format, args.jsonl, format) {
+            Ok(true) => {}
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("Batch failed: {}", e);
+                std::process::exit(2);
+            }
+        }
+        return;
+    }
+    let expr = args.expr.unwrap();
+    let mathml = match args.input_format {
+        InputFormat::Tex => {
+            let output = Command::new("node")
+                .arg(tex::convertor_path())
+                .raw_arg(expr)
+                .output()
+                .unwrap();
+            if !output.status.success() {
+                std::io::stderr().write_all(&output.stdout).unwrap();
+                std::io::stderr().write_all(&output.stderr).unwrap();
+                return;
+            }
+            String::from_utf8(output.stdout).unwrap()
+        }
+        InputFormat::Asciimath => rbmath::asciimath::to_mathml(&expr.to_string_lossy()),
+        InputFormat::Mathml => expr.to_string_lossy().into_owned(),
+    };
+    let res = match rbmath::render_with_options(&mathml, tex::RENDER_OPTIONS) {
+        Ok(res) => res,
+        Err(e) => {
+            eprintln!("Rendering failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+    println!("{}", format_output(&res, format));
+    if args.speech {
+        match rbmath::speech::speak(&mathml, speech_options(args.language, args.verbosity)) {
+            Ok(speech) => println!("{}", speech),
+            Err(e) => eprintln!("Speech rendering failed: {}", e),
+        }
     }
 }
@@ -0,0 +1,221 @@
+use std::{env, fs, io, path::PathBuf};
+
+use clap::{Args, ValueEnum};
+use rbmath::{speech::SpeechOptions, RenderOptions};
+use rustyline::{error::ReadlineError, DefaultEditor};
+
+use crate::{
+    format_output, speech_options, tex, InputFormat, OutputFormat, SpeechLanguage, SpeechVerbosity,
+};
+
+const HELP: &str = "\
+Enter a formula to render it. MathML is recognised by a leading '<' in any input mode.
+Commands:
+  :input tex|asciimath|mathml         notation of the formulas
+  :format all|unicode|dots|brf|latex  one or more output codes to show
+  :speech off|ru|en                   print the spoken form
+  :verbosity brief|medium|verbose     verbosity of the spoken form
+  :decimal comma|point                decimal mark of the numbers
+  :chemistry off|auto|on              read letters as chemical formulas
+  :table <file>                       import a liblouis table
+  :reset                              forget the imported symbols and function names
+  :help                               show this message
+  :quit                               leave the REPL";
+
+#[derive(Args)]
+pub struct ReplArgs {
+    /// File to keep the history in. Defaults to .convertml_history in the home directory.
+    #[arg(long)]
+    history: Option<PathBuf>,
+    /// Notation of the formulas until it is changed with :input.
+    #[arg(id = "input_format", short, long = "input", value_name = "FORMAT", value_enum, default_value_t = InputFormat::Tex)]
+    input_format: InputFormat,
+}
+
+struct Repl {
+    input: InputFormat,
+    formats: Vec<OutputFormat>,
+    options: RenderOptions,
+    speech: Option<SpeechLanguage>,
+    verbosity: SpeechVerbosity,
+}
+
+fn parse_value<T: ValueEnum>(value: &str) -> Result<T, String> {
+    T::from_str(value, true).map_err(|_| {
+        let values: Vec<String> = T::value_variants()
+            .iter()
+            .filter_map(|v| v.to_possible_value())
+            .map(|v| v.get_name().to_string())
+            .collect();
+        format!("expected one of: {}", values.join(", "))
+    })
+}
+
+fn format_name(format: OutputFormat) -> &'static str {
+    match format {
+        OutputFormat::Unicode => "unicode",
+        OutputFormat::Dots => "dots",
+        OutputFormat::Brf => "brf",
+        OutputFormat::Latex => "latex",
+    }
+}
+
+impl Repl {
+    fn new(input: InputFormat, format: Option<OutputFormat>) -> Repl {
+        Repl {
+            input,
+            formats: match format {
+                Some(format) => vec![format],
+                None => OutputFormat::value_variants().to_vec(),
+            },
+            options: tex::RENDER_OPTIONS,
+            speech: None,
+            verbosity: SpeechVerbosity::Medium,
+        }
+    }
+
+    fn prompt(&self) -> &'static str {
+        match self.input {
+            InputFormat::Tex => "tex> ",
+            InputFormat::Asciimath => "asciimath> ",
+            InputFormat::Mathml => "mathml> ",
+        }
+    }
+
+    fn command(&mut self, command: &str, argument: &str) -> Result<(), String> {
+        match command {
+            "input" | "i" => self.input = parse_value(argument)?,
+            "format" | "f" if argument == "all" => {
+                self.formats = OutputFormat::value_variants().to_vec();
+            }
+            "format" | "f" => {
+                self.formats = argument
+                    .split_whitespace()
+                    .map(parse_value)
+                    .collect::<Result<_, _>>()?;
+                if self.formats.is_empty() {
+                    return Err("no output code given".to_string());
+                }
+            }
+            "speech" | "s" if argument == "off" => self.speech = None,
+            "speech" | "s" => self.speech = Some(parse_value(argument)?),
+            "verbosity" | "v" => self.verbosity = parse_value(argument)?,
+            "decimal" | "d" => self.options.decimal_mark = argument.parse()?,
+            "chemistry" | "c" => self.options.chemistry = argument.parse()?,
+            "table" | "t" => {
+                let table = fs::read_to_string(argument).map_err(|e| e.to_string())?;
+                let count = rbmath::liblouis::import_table(&table).map_err(|e| e.to_string())?;
+                println!("Imported {} symbols", count);
+            }
+            "reset" => {
+                rbmath::clear_custom_symbols();
+                rbmath::clear_custom_functions();
+            }
+            "help" | "h" | "?" => println!("{}", HELP),
+            _ => return Err(format!("unknown command :{}, see :help", command)),
+        }
+        Ok(())
+    }
+
+    fn render(&self, line: &str) -> Result<(), String> {
+        let mathml = match self.input {
+            _ if line.starts_with('<') => line.to_string(),
+            InputFormat::Tex => tex::convert_batch(&[line.to_string()])
+                .map_err(|e| e.to_string())?
+                .pop()
+                .unwrap()?,
+            InputFormat::Asciimath => rbmath::asciimath::to_mathml(line),
+            InputFormat::Mathml => line.to_string(),
+        };
+        let res = rbmath::render_with_options(&mathml, self.options).map_err(|e| e.to_string())?;
+        for &format in &self.formats {
+            println!("{:8} {}", format_name(format), format_output(&res, format));
+        }
+        if let Some(language) = self.speech {
+            let options = SpeechOptions {
+                chemistry: self.options.chemistry,
+                ..speech_options(language, self.verbosity)
+            };
+            let speech = rbmath::speech::speak(&mathml, options).map_err(|e| e.to_string())?;
+            println!("{:8} {}", "speech", speech);
+        }
+        Ok(())
+    }
+}
+
+fn default_history() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(".convertml_history"))
+}
+
+pub fn run(args: &ReplArgs, format: Option<OutputFormat>) -> io::Result<()> {
+    // Line editor debug messages would mix with the output.
+    log::set_max_level(log::LevelFilter::Info);
+    let mut editor = DefaultEditor::new().map_err(|e| io::Error::other(e.to_string()))?;
+    let history = args.history.clone().or_else(default_history);
+    if let Some(history) = &history {
+        // There is no history before the first session.
+        let _ = editor.load_history(history);
+    }
+    let mut repl = Repl::new(args.input_format, format);
+    loop {
+        let line = match editor.readline(repl.prompt()) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(io::Error::other(e.to_string())),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line);
+        let res = match line.strip_prefix(':') {
+            Some("quit" | "q" | "exit") => break,
+            Some(command) => {
+                let (command, argument) = command.split_once(' ').unwrap_or((command, ""));
+                repl.command(command, argument.trim())
+            }
+            None => repl.render(line),
+        };
+        if let Err(e) = res {
+            eprintln!("Error: {}", e);
+        }
+    }
+    if let Some(history) = &history {
+        if let Err(e) = editor.save_history(history) {
+            log::warn!("Failed to save history: {}", e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rbmath::{Chemistry, DecimalMark};
+
+    use super::*;
+
+    #[test]
+    fn all_formats_by_default() {
+        let repl = Repl::new(InputFormat::Tex, None);
+        assert_eq!(repl.formats, OutputFormat::value_variants());
+        let repl = Repl::new(InputFormat::Tex, Some(OutputFormat::Dots));
+        assert_eq!(repl.formats, [OutputFormat::Dots]);
+    }
+
+    #[test]
+    fn option_commands() {
+        let mut repl = Repl::new(InputFormat::Tex, None);
+        assert_eq!(repl.options.decimal_mark, DecimalMark::Comma);
+        assert_eq!(repl.options.chemistry, Chemistry::Auto);
+        repl.command("decimal", "point").unwrap();
+        repl.command("chemistry", "on").unwrap();
+        assert_eq!(repl.options.decimal_mark, DecimalMark::Point);
+        assert_eq!(repl.options.chemistry, Chemistry::On);
+        assert!(repl.command("decimal", "dot").is_err());
+        assert!(repl.command("chemistry", "").is_err());
+        assert_eq!(repl.options.chemistry, Chemistry::On);
+    }
+}
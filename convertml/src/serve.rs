@@ -0,0 +1,376 @@
+use std::{
+    io::{self, Read},
+    panic::{self, AssertUnwindSafe},
+    str::FromStr,
+    sync::Arc,
+    thread,
+};
+
+use clap::Args;
+use rbmath::{
+    speech::{Language, SpeechOptions, Verbosity},
+    Chemistry, RenderError, RenderOptions,
+};
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::tex;
+
+#[derive(Args)]
+pub struct ServeArgs {
+    /// Address to listen on. Only local clients can connect by default.
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+    #[arg(short, long, default_value_t = 8090)]
+    port: u16,
+    /// Number of requests handled at the same time.
+    #[arg(long, default_value_t = 4)]
+    threads: usize,
+    /// Largest accepted request body in bytes.
+    #[arg(long, default_value_t = 64 * 1024)]
+    max_size: usize,
+}
+
+struct ApiError {
+    status: u16,
+    code: &'static str,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: u16, code: &'static str, message: impl Into<String>) -> ApiError {
+        ApiError {
+            status,
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<RenderError> for ApiError {
+    fn from(value: RenderError) -> Self {
+        let code = match value {
+            RenderError::Xml(_) => "invalid_xml",
+            RenderError::NotMathML(_) => "not_mathml",
+        };
+        ApiError::new(422, code, value.to_string())
+    }
+}
+
+fn read_body(request: &mut Request, max_size: usize) -> Result<String, ApiError> {
+    let too_large = || {
+        ApiError::new(
+            413,
+            "payload_too_large",
+            format!("request body exceeds {} bytes", max_size),
+        )
+    };
+    if request.body_length().is_some_and(|l| l > max_size) {
+        return Err(too_large());
+    }
+    // The declared length can be missing, so never read more than the limit.
+    let mut body = Vec::new();
+    request
+        .as_reader()
+        .take(max_size as u64 + 1)
+        .read_to_end(&mut body)
+        .map_err(|e| ApiError::new(400, "invalid_request", e.to_string()))?;
+    if body.len() > max_size {
+        return Err(too_large());
+    }
+    String::from_utf8(body).map_err(|_| ApiError::new(400, "invalid_request", "body is not UTF-8"))
+}
+
+fn parse_option<T: FromStr<Err = String>>(
+    request: &Value,
+    field: &str,
+) -> Result<Option<T>, ApiError> {
+    request
+        .get(field)
+        .and_then(Value::as_str)
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|e| ApiError::new(400, "invalid_option", e))
+        })
+        .transpose()
+}
+
+fn render_options(request: &Value) -> Result<RenderOptions, ApiError> {
+    let defaults = tex::RENDER_OPTIONS;
+    Ok(RenderOptions {
+        decimal_mark: parse_option(request, "decimal_mark")?.unwrap_or(defaults.decimal_mark),
+        chemistry: parse_option(request, "chemistry")?.unwrap_or(defaults.chemistry),
+    })
+}
+
+fn speech_options(
+    request: &Value,
+    chemistry: Chemistry,
+) -> Result<Option<SpeechOptions>, ApiError> {
+    let language = match request.get("speech").and_then(Value::as_str) {
+        None => return Ok(None),
+        Some("ru") => Language::Russian,
+        Some("en") => Language::English,
+        Some(other) => {
+            return Err(ApiError::new(
+                400,
+                "invalid_option",
+                format!("unknown speech language {}", other),
+            ))
+        }
+    };
+    let verbosity = match request.get("verbosity").and_then(Value::as_str) {
+        None | Some("medium") => Verbosity::Medium,
+        Some("brief") => Verbosity::Brief,
+        Some("verbose") => Verbosity::Verbose,
+        Some(other) => {
+            return Err(ApiError::new(
+                400,
+                "invalid_option",
+                format!("unknown verbosity {}", other),
+            ))
+        }
+    };
+    Ok(Some(SpeechOptions {
+        language,
+        verbosity,
+        chemistry,
+    }))
+}
+
+fn render(body: &str) -> Result<Value, ApiError> {
+    let request: Value = serde_json::from_str(body)
+        .map_err(|e| ApiError::new(400, "invalid_json", e.to_string()))?;
+    let text = |field| request.get(field).and_then(Value::as_str);
+    let (mathml, converted) = if let Some(mathml) = text("mathml") {
+        (mathml.to_string(), false)
+    } else if let Some(tex) = text("tex") {
+        let mathml = tex::convert_batch(&[tex.to_string()])
+            .map_err(|e| ApiError::new(500, "convertor_failed", e.to_string()))?
+            .pop()
+            .unwrap()
+            .map_err(|e| ApiError::new(422, "invalid_tex", e))?;
+        (mathml, true)
+    } else if let Some(asciimath) = text("asciimath") {
+        (rbmath::asciimath::to_mathml(asciimath), true)
+    } else {
+        return Err(ApiError::new(
+            400,
+            "missing_input",
+            "expected a mathml, tex or asciimath field",
+        ));
+    };
+    let options = render_options(&request)?;
+    let speech = speech_options(&request, options.chemistry)?;
+    let (braille, spans) = rbmath::render_with_spans_and_options(&mathml, options)?;
+    let mut res = json!({
+        "unicode": braille.to_unicode(),
+        "dots": braille.to_dots(),
+        "brf": braille.to_brf(),
+        "spans": spans
+            .iter()
+            .map(|s| json!({"cells": [s.cells.start, s.cells.end], "source": [s.source.start, s.source.end]}))
+            .collect::<Vec<_>>(),
+    });
+    // Spans of converted input point into the generated MathML.
+    if converted {
+        res["mathml"] = Value::from(mathml.as_str());
+    }
+    if let Some(options) = speech {
+        res["speech"] = Value::from(rbmath::speech::speak(&mathml, options)?);
+    }
+    Ok(res)
+}
+
+fn handle(request: &mut Request, max_size: usize) -> Result<Value, ApiError> {
+    match (request.method(), request.url()) {
+        (Method::Get, "/health") => Ok(json!({"status": "ok"})),
+        (Method::Post, "/render") => {
+            let body = read_body(request, max_size)?;
+            panic::catch_unwind(AssertUnwindSafe(|| render(&body)))
+                .unwrap_or_else(|_| Err(ApiError::new(500, "internal", "rendering panicked")))
+        }
+        (_, "/health" | "/render") => Err(ApiError::new(
+            405,
+            "method_not_allowed",
+            "use POST /render or GET /health",
+        )),
+        (_, url) => Err(ApiError::new(
+            404,
+            "not_found",
+            format!("no endpoint {}", url),
+        )),
+    }
+}
+
+fn respond(mut request: Request, max_size: usize) {
+    let (status, body) = match handle(&mut request, max_size) {
+        Ok(res) => (200, res),
+        Err(e) => (
+            e.status,
+            json!({"error": {"code": e.code, "message": e.message}}),
+        ),
+    };
+    log::info!("{} {} -> {}", request.method(), request.url(), status);
+    let header = Header::from_bytes(
+        &b"Content-Type"[..],
+        &b"application/json; charset=utf-8"[..],
+    )
+    .unwrap();
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(header);
+    if let Err(e) = request.respond(response) {
+        log::warn!("Failed to send response: {}", e);
+    }
+}
+
+pub fn run(args: &ServeArgs) -> io::Result<()> {
+    // The server logs every connection at the debug level.
+    log::set_max_level(log::LevelFilter::Info);
+    let server = Server::http((args.host.as_str(), args.port))
+        .map(Arc::new)
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    log::info!("Listening on http://{}:{}", args.host, args.port);
+    let workers: Vec<_> = (0..args.threads.max(1))
+        .map(|_| {
+            let server = server.clone();
+            let max_size = args.max_size;
+            thread::spawn(move || {
+                while let Ok(request) = server.recv() {
+                    respond(request, max_size);
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        let _ = worker.join();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tiny_http::TestRequest;
+
+    use super::*;
+
+    fn post(body: &'static str, max_size: usize) -> Result<Value, ApiError> {
+        let mut request = TestRequest::new()
+            .with_method(Method::Post)
+            .with_path("/render")
+            .with_body(body)
+            .into();
+        handle(&mut request, max_size)
+    }
+
+    fn error(res: Result<Value, ApiError>) -> (u16, &'static str) {
+        let e = res.expect_err("request should fail");
+        (e.status, e.code)
+    }
+
+    #[test]
+    fn renders_mathml() {
+        let res = post(
+            r#"{"mathml": "<math><mi>x</mi><mo>+</mo><mn>1</mn></math>", "speech": "ru"}"#,
+            1024,
+        )
+        .ok()
+        .unwrap();
+        assert_eq!(res["unicode"], "⠠⠭⠀⠖⠼⠁");
+        assert_eq!(res["dots"], "6 1346 0 235 3456 1");
+        assert_eq!(res["brf"], ",X 6#A");
+        assert_eq!(res["spans"][1], json!({"cells": [0, 2], "source": [6, 16]}));
+        assert_eq!(res["speech"], "икс плюс один");
+        assert!(res.get("mathml").is_none());
+        let res = post(r#"{"asciimath": "x+1"}"#, 1024).ok().unwrap();
+        assert_eq!(res["dots"], "6 1346 0 235 3456 1");
+        assert!(res["mathml"].as_str().unwrap().starts_with("<math"));
+    }
+
+    #[test]
+    fn option_fields() {
+        let number = |options: &str| {
+            let body = format!(
+                r#"{{"mathml": "<math><mn>1,000.5</mn></math>"{}}}"#,
+                options
+            );
+            render(&body).ok().unwrap()["dots"].clone()
+        };
+        assert_eq!(number(""), "3456 1 2 245 245 245 2 15");
+        assert_eq!(
+            number(r#", "decimal_mark": "point""#),
+            "3456 1 3 245 245 245 2 15"
+        );
+        let water = |chemistry: &str| {
+            let body = format!(
+                r#"{{"mathml": "<math><mi>H</mi><mn>2</mn><mi>O</mi></math>", "speech": "ru", "chemistry": "{}"}}"#,
+                chemistry
+            );
+            let res = render(&body).ok().unwrap();
+            (res["dots"].clone(), res["speech"].clone())
+        };
+        assert_eq!(
+            water("off"),
+            (
+                "46 125 3456 12 135".into(),
+                "заглавная аш два заглавная о".into()
+            )
+        );
+        assert_eq!(
+            water("on"),
+            ("46 125 3456 12 46 135".into(), "аш два о".into())
+        );
+        for body in [
+            r#"{"mathml": "<math/>", "decimal_mark": "dot"}"#,
+            r#"{"mathml": "<math/>", "chemistry": "yes"}"#,
+            r#"{"mathml": "<math/>", "speech": "de"}"#,
+            r#"{"mathml": "<math/>", "speech": "ru", "verbosity": "long"}"#,
+        ] {
+            assert_eq!(error(render(body)), (400, "invalid_option"), "{}", body);
+        }
+    }
+
+    #[test]
+    fn request_errors() {
+        assert_eq!(error(post("{", 1024)), (400, "invalid_json"));
+        assert_eq!(
+            error(post(r#"{"latex": "x"}"#, 1024)),
+            (400, "missing_input")
+        );
+        assert_eq!(
+            error(post(r#"{"mathml": "<math><mi>x</math>"}"#, 1024)),
+            (422, "invalid_xml")
+        );
+        assert_eq!(
+            error(post(r#"{"mathml": "<svg/>"}"#, 1024)),
+            (422, "not_mathml")
+        );
+        let body = r#"{"mathml": "<math><mi>x</mi></math>"}"#;
+        assert!(post(body, body.len()).is_ok());
+        assert_eq!(
+            error(post(body, body.len() - 1)),
+            (413, "payload_too_large")
+        );
+    }
+
+    #[test]
+    fn routes() {
+        let get = |path: &str| {
+            let mut request = TestRequest::new().with_path(path).into();
+            handle(&mut request, 1024)
+        };
+        assert_eq!(get("/health").ok().unwrap(), json!({"status": "ok"}));
+        assert_eq!(error(get("/render")), (405, "method_not_allowed"));
+        assert_eq!(error(get("/")), (404, "not_found"));
+        let mut request = TestRequest::new()
+            .with_method(Method::Post)
+            .with_path("/health")
+            .into();
+        assert_eq!(
+            error(handle(&mut request, 1024)),
+            (405, "method_not_allowed")
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use std::{
+    env,
+    io::{self, BufRead, BufReader, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+    thread,
+};
+
+use rbmath::{Chemistry, DecimalMark, RenderOptions};
+use serde_json::Value;
+
+// Formulas converted from TeX may be written with mhchem.
+pub const RENDER_OPTIONS: RenderOptions = RenderOptions {
+    decimal_mark: DecimalMark::Comma,
+    chemistry: Chemistry::Auto,
+};
+
+pub fn convertor_path() -> PathBuf {
+    env::current_exe()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join("convertor.js")
+}
+
+// Converts every formula to MathML with a single node process, keeping the order of the input.
+pub fn convert_batch(formulas: &[String]) -> io::Result<Vec<Result<String, String>>> {
+    if formulas.is_empty() {
+        return Ok(Vec::new());
+    }
+    let lines: Vec<String> = formulas
+        .iter()
+        .map(|f| Value::from(f.as_str()).to_string())
+        .collect();
+    let mut child = Command::new("node")
+        .arg(convertor_path())
+        .arg("--batch")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let mut stdin = child.stdin.take().unwrap();
+    // Write from a separate thread, otherwise both processes could block on full pipes.
+    let writer = thread::spawn(move || -> io::Result<()> {
+        for l in lines {
+            writeln!(stdin, "{}", l)?;
+        }
+        Ok(())
+    });
+    let mut res = Vec::with_capacity(formulas.len());
+    for line in BufReader::new(child.stdout.take().unwrap())
+        .lines()
+        .take(formulas.len())
+    {
+        let value: Value = serde_json::from_str(&line?)?;
+        res.push(match (value.get("mathml"), value.get("error")) {
+            (Some(Value::String(ml)), _) => Ok(ml.clone()),
+            (_, Some(Value::String(e))) => Err(e.clone()),
+            _ => Err("unexpected convertor output".to_string()),
+        });
+    }
+    writer.join().unwrap()?;
+    child.wait()?;
+    if res.len() != formulas.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "convertor exited prematurely",
+        ));
+    }
+    Ok(res)
+}
@@ -0,0 +1,119 @@
+#ifndef RBMATH_H
+#define RBMATH_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef enum {
+  RBMATH_CHEMISTRY_OFF = 0,
+  /**
+   * Letters form chemical formulas only where the expression looks like one.
+   */
+  RBMATH_CHEMISTRY_AUTO = 1,
+  RBMATH_CHEMISTRY_ON = 2,
+} RbmathChemistry;
+
+typedef enum {
+  RBMATH_DECIMAL_MARK_COMMA = 0,
+  RBMATH_DECIMAL_MARK_POINT = 1,
+} RbmathDecimalMark;
+
+typedef enum {
+  /**
+   * Unicode braille patterns (U+2800 block).
+   */
+  RBMATH_OUTPUT_UNICODE = 0,
+  /**
+   * Dot numbers of every cell separated by spaces, with 0 standing for an empty cell.
+   */
+  RBMATH_OUTPUT_DOTS = 1,
+  /**
+   * North American Braille ASCII, as in BRF files.
+   */
+  RBMATH_OUTPUT_BRF = 2,
+} RbmathOutput;
+
+typedef enum {
+  RBMATH_STATUS_OK = 0,
+  RBMATH_STATUS_NULL_ARGUMENT = 1,
+  RBMATH_STATUS_INVALID_UTF8 = 2,
+  RBMATH_STATUS_INVALID_XML = 3,
+  RBMATH_STATUS_NOT_MATHML = 4,
+  RBMATH_STATUS_INTERNAL = 5,
+  RBMATH_STATUS_INVALID_OPTION = 6,
+} RbmathStatus;
+
+/**
+ * Zero-initialised options give Unicode output with the default rendering options.
+ * The fields are plain integers, because C may store any value in them; unknown values are rejected
+ * with `RBMATH_STATUS_INVALID_OPTION`.
+ */
+typedef struct {
+  /**
+   * One of `RbmathOutput`.
+   */
+  uint32_t output;
+  /**
+   * One of `RbmathDecimalMark`.
+   */
+  uint32_t decimal_mark;
+  /**
+   * One of `RbmathChemistry`.
+   */
+  uint32_t chemistry;
+} RbmathOptions;
+
+typedef struct {
+  RbmathStatus status;
+  /**
+   * Human readable description, owned by the library. Release it with `rbmath_error_free`.
+   */
+  char *message;
+} RbmathError;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Renders a MathML document into braille.
+ *
+ * On success `*out` receives a nul-terminated UTF-8 string which must be released with `rbmath_string_free`.
+ * On failure `*out` is set to NULL and, if `error` is not NULL, it is filled with the failure details.
+ *
+ * # Safety
+ *
+ * `mathml` must be a valid nul-terminated string and `out` a valid pointer.
+ * `options` and `error` may be NULL; otherwise they must point to valid structures.
+ */
+RbmathStatus rbmath_render(const char *mathml,
+                           const RbmathOptions *options,
+                           char **out,
+                           RbmathError *error);
+
+/**
+ * Releases a string returned by `rbmath_render`. Passing NULL is allowed.
+ *
+ * # Safety
+ *
+ * `s` must be NULL or a pointer obtained from this library that hasn't been freed yet.
+ */
+void rbmath_string_free(char *s);
+
+/**
+ * Releases the message stored in `error` and resets it to `RBMATH_STATUS_OK`. Passing NULL is allowed.
+ *
+ * # Safety
+ *
+ * `error` must be NULL or point to a structure filled by `rbmath_render`.
+ */
+void rbmath_error_free(RbmathError *error);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* RBMATH_H */
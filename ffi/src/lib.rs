@@ -0,0 +1,198 @@
+use std::{
+    ffi::{c_char, CStr, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr,
+};
+
+use rbmath::{Chemistry, DecimalMark, RenderError, RenderOptions};
+
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum RbmathStatus {
+    Ok = 0,
+    NullArgument = 1,
+    InvalidUtf8 = 2,
+    InvalidXml = 3,
+    NotMathml = 4,
+    Internal = 5,
+    InvalidOption = 6,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum RbmathOutput {
+    /// Unicode braille patterns (U+2800 block).
+    Unicode = 0,
+    /// Dot numbers of every cell separated by spaces, with 0 standing for an empty cell.
+    Dots = 1,
+    /// North American Braille ASCII, as in BRF files.
+    Brf = 2,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum RbmathDecimalMark {
+    Comma = 0,
+    Point = 1,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum RbmathChemistry {
+    Off = 0,
+    /// Letters form chemical formulas only where the expression looks like one.
+    Auto = 1,
+    On = 2,
+}
+
+/// Zero-initialised options give Unicode output with the default rendering options.
+/// The fields are plain integers, because C may store any value in them; unknown values are rejected
+/// with `RBMATH_STATUS_INVALID_OPTION`.
+#[repr(C)]
+pub struct RbmathOptions {
+    /// One of `RbmathOutput`.
+    pub output: u32,
+    /// One of `RbmathDecimalMark`.
+    pub decimal_mark: u32,
+    /// One of `RbmathChemistry`.
+    pub chemistry: u32,
+}
+
+#[repr(C)]
+pub struct RbmathError {
+    pub status: RbmathStatus,
+    /// Human readable description, owned by the library. Release it with `rbmath_error_free`.
+    pub message: *mut c_char,
+}
+
+impl From<&RenderError> for RbmathStatus {
+    fn from(value: &RenderError) -> Self {
+        match value {
+            RenderError::Xml(_) => RbmathStatus::InvalidXml,
+            RenderError::NotMathML(_) => RbmathStatus::NotMathml,
+        }
+    }
+}
+
+fn parse_options(options: &RbmathOptions) -> Result<(RbmathOutput, RenderOptions), String> {
+    let output = match options.output {
+        0 => RbmathOutput::Unicode,
+        1 => RbmathOutput::Dots,
+        2 => RbmathOutput::Brf,
+        n => return Err(format!("unknown output {}", n)),
+    };
+    let decimal_mark = match options.decimal_mark {
+        0 => DecimalMark::Comma,
+        1 => DecimalMark::Point,
+        n => return Err(format!("unknown decimal mark {}", n)),
+    };
+    let chemistry = match options.chemistry {
+        0 => Chemistry::Off,
+        1 => Chemistry::Auto,
+        2 => Chemistry::On,
+        n => return Err(format!("unknown chemistry mode {}", n)),
+    };
+    Ok((
+        output,
+        RenderOptions {
+            decimal_mark,
+            chemistry,
+        },
+    ))
+}
+
+fn to_c_string(s: String) -> *mut c_char {
+    // Neither braille nor error messages contain interior nuls, but don't trust that blindly.
+    CString::new(s.replace('\0', ""))
+        .map(CString::into_raw)
+        .unwrap_or(ptr::null_mut())
+}
+
+unsafe fn report(error: *mut RbmathError, status: RbmathStatus, message: String) -> RbmathStatus {
+    if let Some(error) = error.as_mut() {
+        error.status = status;
+        error.message = if status == RbmathStatus::Ok {
+            ptr::null_mut()
+        } else {
+            to_c_string(message)
+        };
+    }
+    status
+}
+
+/// Renders a MathML document into braille.
+///
+/// On success `*out` receives a nul-terminated UTF-8 string which must be released with `rbmath_string_free`.
+/// On failure `*out` is set to NULL and, if `error` is not NULL, it is filled with the failure details.
+///
+/// # Safety
+///
+/// `mathml` must be a valid nul-terminated string and `out` a valid pointer.
+/// `options` and `error` may be NULL; otherwise they must point to valid structures.
+#[no_mangle]
+pub unsafe extern "C" fn rbmath_render(
+    mathml: *const c_char,
+    options: *const RbmathOptions,
+    out: *mut *mut c_char,
+    error: *mut RbmathError,
+) -> RbmathStatus {
+    if out.is_null() {
+        return report(error, RbmathStatus::NullArgument, "out is NULL".to_string());
+    }
+    *out = ptr::null_mut();
+    if mathml.is_null() {
+        return report(error, RbmathStatus::NullArgument, "mathml is NULL".to_string());
+    }
+    let Ok(mathml) = CStr::from_ptr(mathml).to_str() else {
+        return report(error, RbmathStatus::InvalidUtf8, "mathml is not valid UTF-8".to_string());
+    };
+    let (output, render_options) = match options.as_ref().map(parse_options) {
+        None => (RbmathOutput::Unicode, RenderOptions::default()),
+        Some(Ok(options)) => options,
+        Some(Err(message)) => return report(error, RbmathStatus::InvalidOption, message),
+    };
+    match panic::catch_unwind(AssertUnwindSafe(|| {
+        rbmath::render_with_options(mathml, render_options)
+    })) {
+        Ok(Ok(res)) => {
+            *out = to_c_string(match output {
+                RbmathOutput::Unicode => res.to_unicode(),
+                RbmathOutput::Dots => res.to_dots(),
+                RbmathOutput::Brf => res.to_brf(),
+            });
+            report(error, RbmathStatus::Ok, String::new())
+        }
+        Ok(Err(e)) => report(error, (&e).into(), e.to_string()),
+        Err(_) => report(
+            error,
+            RbmathStatus::Internal,
+            "rendering panicked".to_string(),
+        ),
+    }
+}
+
+/// Releases a string returned by `rbmath_render`. Passing NULL is allowed.
+///
+/// # Safety
+///
+/// `s` must be NULL or a pointer obtained from this library that hasn't been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn rbmath_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Releases the message stored in `error` and resets it to `RBMATH_STATUS_OK`. Passing NULL is allowed.
+///
+/// # Safety
+///
+/// `error` must be NULL or point to a structure filled by `rbmath_render`.
+#[no_mangle]
+pub unsafe extern "C" fn rbmath_error_free(error: *mut RbmathError) {
+    if let Some(error) = error.as_mut() {
+        rbmath_string_free(error.message);
+        error.message = ptr::null_mut();
+        error.status = RbmathStatus::Ok;
+    }
+}
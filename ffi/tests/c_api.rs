@@ -0,0 +1,38 @@
+// Compiles tests/render.c against the generated header and the cdylib built by cargo, then runs it.
+#![cfg(unix)]
+
+use std::{env, path::PathBuf, process::Command};
+
+#[test]
+fn c_api() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // The test binary lives in target/<profile>/deps. `cargo test` leaves the cdylib there,
+    // `cargo build` also copies it one level up.
+    let deps_dir = env::current_exe().unwrap().parent().unwrap().to_path_buf();
+    let lib_name = format!(
+        "{}rbmath_ffi{}",
+        env::consts::DLL_PREFIX,
+        env::consts::DLL_SUFFIX
+    );
+    let lib_dir = [deps_dir.clone(), deps_dir.parent().unwrap().to_path_buf()]
+        .into_iter()
+        .find(|dir| dir.join(&lib_name).exists())
+        .unwrap_or_else(|| panic!("{} not found near {}", lib_name, deps_dir.display()));
+    let exe = lib_dir.join("rbmath_ffi_c_test");
+    let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(compiler)
+        .arg(manifest_dir.join("tests/render.c"))
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg("-L")
+        .arg(&lib_dir)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .arg("-lrbmath_ffi")
+        .arg("-o")
+        .arg(&exe)
+        .status()
+        .expect("failed to run the C compiler");
+    assert!(status.success(), "compiling render.c failed");
+    let status = Command::new(&exe).status().unwrap();
+    assert!(status.success(), "render.c reported failures");
+}
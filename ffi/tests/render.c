@@ -0,0 +1,64 @@
+#include <stdio.h>
+#include <string.h>
+
+#include "rbmath.h"
+
+static int failures = 0;
+
+static void check(int condition, const char *what) {
+    if (!condition) {
+        fprintf(stderr, "FAILED: %s\n", what);
+        failures++;
+    }
+}
+
+int main(void) {
+    RbmathOptions options = {RBMATH_OUTPUT_DOTS};
+    RbmathError error = {RBMATH_STATUS_OK, NULL};
+    char *out = NULL;
+
+    RbmathStatus status = rbmath_render("<math><mn>12</mn></math>", &options, &out, &error);
+    check(status == RBMATH_STATUS_OK, "valid MathML renders");
+    check(out != NULL && strcmp(out, "3456 1 12") == 0, "dots output");
+    check(error.message == NULL, "no message on success");
+    rbmath_string_free(out);
+
+    status = rbmath_render("<math><mi>x</mi></math>", NULL, &out, NULL);
+    check(status == RBMATH_STATUS_OK, "default options");
+    check(out != NULL && strcmp(out, "\xe2\xa0\xa0\xe2\xa0\xad") == 0, "unicode output");
+    rbmath_string_free(out);
+
+    RbmathOptions brf = {RBMATH_OUTPUT_BRF, RBMATH_DECIMAL_MARK_COMMA, RBMATH_CHEMISTRY_OFF};
+    status = rbmath_render("<math><mn>12</mn></math>", &brf, &out, &error);
+    check(status == RBMATH_STATUS_OK && strcmp(out, "#AB") == 0, "BRF output");
+    rbmath_string_free(out);
+
+    RbmathOptions chemistry = {RBMATH_OUTPUT_DOTS, RBMATH_DECIMAL_MARK_POINT, RBMATH_CHEMISTRY_ON};
+    status = rbmath_render("<math><mi>N</mi><mi>a</mi><mi>C</mi><mi>l</mi></math>", &chemistry, &out, &error);
+    check(status == RBMATH_STATUS_OK && strcmp(out, "46 1345 1 46 14 123") == 0, "chemistry option");
+    rbmath_string_free(out);
+
+    RbmathOptions invalid = {RBMATH_OUTPUT_DOTS, 7, RBMATH_CHEMISTRY_OFF};
+    status = rbmath_render("<math><mn>1</mn></math>", &invalid, &out, &error);
+    check(status == RBMATH_STATUS_INVALID_OPTION, "unknown option values are rejected");
+    check(out == NULL, "no output for invalid options");
+    rbmath_error_free(&error);
+
+    status = rbmath_render("<math><mi>x</mi>", &options, &out, &error);
+    check(status == RBMATH_STATUS_INVALID_XML, "invalid XML is reported");
+    check(error.status == RBMATH_STATUS_INVALID_XML, "error status is filled");
+    check(error.message != NULL, "error message is filled");
+    check(out == NULL, "no output on failure");
+    rbmath_error_free(&error);
+    check(error.message == NULL, "message is released");
+
+    status = rbmath_render("<html/>", &options, &out, &error);
+    check(status == RBMATH_STATUS_NOT_MATHML, "non-MathML root is reported");
+    rbmath_error_free(&error);
+
+    status = rbmath_render(NULL, &options, &out, &error);
+    check(status == RBMATH_STATUS_NULL_ARGUMENT, "NULL input is reported");
+    rbmath_error_free(&error);
+
+    return failures == 0 ? 0 : 1;
+}
@@ -0,0 +1,83 @@
+use std::{env, path::PathBuf, process::Command};
+
+use brl::BrailleString;
+use pyo3::{
+    create_exception,
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+};
+use rbmath::RenderOptions;
+
+create_exception!(rbmath, RenderError, PyValueError);
+
+fn format_output(res: &BrailleString, output: &str) -> PyResult<String> {
+    match output {
+        "unicode" => Ok(res.to_unicode()),
+        "dots" => Ok(res.to_dots()),
+        "brf" => Ok(res.to_brf()),
+        other => Err(PyValueError::new_err(format!(
+            "unknown output {}, expected unicode, dots or brf",
+            other
+        ))),
+    }
+}
+
+fn render_options(decimal_mark: &str, chemistry: &str) -> PyResult<RenderOptions> {
+    Ok(RenderOptions {
+        decimal_mark: decimal_mark.parse().map_err(PyValueError::new_err)?,
+        chemistry: chemistry.parse().map_err(PyValueError::new_err)?,
+    })
+}
+
+/// Renders a MathML document into braille.
+///
+/// output selects the representation: "unicode" (default), "dots" or "brf".
+/// decimal_mark is "comma" (default) or "point"; chemistry is "off", "auto" (default, as in convertml) or "on".
+#[pyfunction]
+#[pyo3(signature = (mathml, *, output = "unicode", decimal_mark = "comma", chemistry = "auto"))]
+fn render(mathml: &str, output: &str, decimal_mark: &str, chemistry: &str) -> PyResult<String> {
+    let options = render_options(decimal_mark, chemistry)?;
+    let res = rbmath::render_with_options(mathml, options)
+        .map_err(|e| RenderError::new_err(e.to_string()))?;
+    format_output(&res, output)
+}
+
+/// Converts a TeX formula to MathML with the temml based convertor from convertml and renders it.
+///
+/// The convertor script is taken from the convertor argument or the RBMATH_CONVERTOR environment variable;
+/// Node.js has to be on the PATH. The other options are the same as for render.
+#[pyfunction]
+#[pyo3(signature = (tex, *, output = "unicode", decimal_mark = "comma", chemistry = "auto", convertor = None))]
+fn from_latex(
+    tex: &str,
+    output: &str,
+    decimal_mark: &str,
+    chemistry: &str,
+    convertor: Option<PathBuf>,
+) -> PyResult<String> {
+    let Some(convertor) = convertor.or_else(|| env::var_os("RBMATH_CONVERTOR").map(PathBuf::from))
+    else {
+        return Err(PyRuntimeError::new_err(
+            "LaTeX front end isn't available: pass convertor or set RBMATH_CONVERTOR",
+        ));
+    };
+    let res = Command::new("node")
+        .arg(&convertor)
+        .arg(tex)
+        .output()
+        .map_err(|e| PyRuntimeError::new_err(format!("failed to run node: {}", e)))?;
+    let stdout = String::from_utf8_lossy(&res.stdout);
+    if !res.status.success() {
+        return Err(RenderError::new_err(stdout.trim().to_string()));
+    }
+    render(&stdout, output, decimal_mark, chemistry)
+}
+
+#[pymodule]
+#[pyo3(name = "rbmath")]
+fn rbmath_module(py: Python, m: &PyModule) -> PyResult<()> {
+    m.add("RenderError", py.get_type::<RenderError>())?;
+    m.add_function(wrap_pyfunction!(render, m)?)?;
+    m.add_function(wrap_pyfunction!(from_latex, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,51 @@
+import pytest
+
+import rbmath
+
+X = "<math><mi>x</mi></math>"
+
+
+def test_outputs():
+    assert rbmath.render(X) == "⠠⠭"
+    assert rbmath.render(X, output="unicode") == "⠠⠭"
+    assert rbmath.render(X, output="dots") == "6 1346"
+    assert rbmath.render("<math><mn>12</mn></math>", output="brf") == "#AB"
+
+
+def test_decimal_mark():
+    number = "<math><mn>1,5</mn></math>"
+    assert rbmath.render(number, output="dots") == "3456 1 2 15"
+    # With a decimal point the comma separates digit groups.
+    assert rbmath.render(number, output="dots", decimal_mark="point") == "3456 1 3 15"
+
+
+def test_chemistry():
+    salt = "<math><mi>N</mi><mi>a</mi><mi>C</mi><mi>l</mi></math>"
+    assert rbmath.render(salt, output="dots", chemistry="off") == "46 1345 6 1 46 14 6 123"
+    assert rbmath.render(salt, output="dots", chemistry="on") == "46 1345 1 46 14 123"
+
+
+def test_invalid_options():
+    with pytest.raises(ValueError):
+        rbmath.render(X, output="html")
+    with pytest.raises(ValueError):
+        rbmath.render(X, decimal_mark="dot")
+    with pytest.raises(ValueError):
+        rbmath.render(X, chemistry="maybe")
+    with pytest.raises(TypeError):
+        rbmath.render(X, braille="ru")
+
+
+def test_render_errors():
+    with pytest.raises(rbmath.RenderError):
+        rbmath.render("<math><mi>x</mi>")
+    with pytest.raises(rbmath.RenderError, match="unexpected root element"):
+        rbmath.render("<html/>")
+    # RenderError is a ValueError, so callers can catch both kinds of bad input together.
+    assert issubclass(rbmath.RenderError, ValueError)
+
+
+def test_from_latex_without_convertor(monkeypatch):
+    monkeypatch.delenv("RBMATH_CONVERTOR", raising=False)
+    with pytest.raises(RuntimeError):
+        rbmath.from_latex(r"\sqrt{x}")
@@ -0,0 +1,707 @@
+// AsciiMath (asciimath.org) front end. Formulas are converted to MathML, so they take the same path as TeX input.
+use std::fmt::Write;
+
+use brl::BrailleString;
+
+use crate::{symbol_tables, RenderError};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum UnaryOp {
+    Sqrt,
+    Over(&'static str),
+    Under(&'static str),
+    Fence(&'static str, &'static str),
+    Font(u32, u32),
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum BinaryOp {
+    Frac,
+    Root,
+    Over,
+    Under,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Kind {
+    Number,
+    Ident,
+    Operator,
+    Function,
+    // Operators whose scripts are placed under and over them, such as sums and limits.
+    UnderOver,
+    LeftBracket,
+    RightBracket,
+    Bar,
+    Unary(UnaryOp),
+    Binary(BinaryOp),
+    Text,
+}
+
+#[derive(Clone, Debug)]
+struct Token<'a> {
+    kind: Kind,
+    text: &'a str,
+}
+
+const SYMBOLS: &[(&[&str], Kind, &str)] = {
+    use Kind::*;
+    &[
+        (&["+"], Operator, "+"),
+        (&["-"], Operator, "-"),
+        (&["*", "cdot"], Operator, "⋅"),
+        (&["**", "ast"], Operator, "∗"),
+        (&["***", "star"], Operator, "⋆"),
+        (&["//"], Operator, "/"),
+        (&["\\\\", "backslash"], Operator, "\\"),
+        (&["xx", "times"], Operator, "×"),
+        (&["-:", "div", "divide"], Operator, "÷"),
+        (&["@", "circ"], Operator, "∘"),
+        (&["o+", "oplus"], Operator, "⊕"),
+        (&["ox", "otimes"], Operator, "⊗"),
+        (&["o.", "odot"], Operator, "⊙"),
+        (&["^^", "wedge"], Operator, "∧"),
+        (&["vv", "vee"], Operator, "∨"),
+        (&["nn", "cap"], Operator, "∩"),
+        (&["uu", "cup"], Operator, "∪"),
+        (&["+-", "pm"], Operator, "±"),
+        (&["-+", "mp"], Operator, "∓"),
+        (&["="], Operator, "="),
+        (&["!=", "ne"], Operator, "≠"),
+        (&["<", "lt"], Operator, "<"),
+        (&[">", "gt"], Operator, ">"),
+        (&["<=", "le"], Operator, "≤"),
+        (&[">=", "ge", "geq"], Operator, "≥"),
+        (&["leq"], Operator, "≤"),
+        (&["-<", "prec"], Operator, "≺"),
+        (&[">-", "succ"], Operator, "≻"),
+        (&["in"], Operator, "∈"),
+        (&["!in", "notin"], Operator, "∉"),
+        (&["sub", "subset"], Operator, "⊂"),
+        (&["sup", "supset"], Operator, "⊃"),
+        (&["sube", "subseteq"], Operator, "⊆"),
+        (&["supe", "supseteq"], Operator, "⊇"),
+        (&["-=", "equiv"], Operator, "≡"),
+        (&["~=", "cong"], Operator, "≅"),
+        (&["~~", "approx"], Operator, "≈"),
+        (&["~", "sim"], Operator, "~"),
+        (&["prop", "propto"], Operator, "∝"),
+        (&["not", "neg"], Operator, "¬"),
+        (&["=>", "implies"], Operator, "⇒"),
+        (&["<=>", "iff"], Operator, "⇔"),
+        (&["AA", "forall"], Operator, "∀"),
+        (&["EE", "exists"], Operator, "∃"),
+        (&["_, _", "bot"], Operator, "⊥"),
+        (&["TT", "top"], Operator, "⊤"),
+        (&[", --", "vdash"], Operator, "⊢"),
+        (&[", ==", "models"], Operator, "⊨"),
+        (&["del", "partial"], Operator, "∂"),
+        (&["grad", "nabla"], Operator, "∇"),
+        (&["int"], Operator, "∫"),
+        (&["oint"], Operator, "∮"),
+        (&["O/", "emptyset"], Operator, "∅"),
+        (&["oo", "infty"], Operator, "∞"),
+        (&["aleph"], Operator, "ℵ"),
+        (&["/_", "angle"], Operator, "∠"),
+        (&["/_\\", "triangle"], Operator, "△"),
+        (&[":.", "therefore"], Operator, "∴"),
+        (&[":'", "because"], Operator, "∵"),
+        (&["...", "ldots"], Operator, "…"),
+        (&["cdots"], Operator, "⋯"),
+        (&["vdots"], Operator, "⋮"),
+        (&["ddots"], Operator, "⋱"),
+        (&["'", "prime"], Operator, "′"),
+        (&[","], Operator, ","),
+        (&["."], Operator, "."),
+        (&[";"], Operator, ";"),
+        (&[":"], Operator, ":"),
+        (&["!"], Operator, "!"),
+        (&["%"], Operator, "%"),
+        (&["/"], Operator, "/"),
+        (&["uarr", "uparrow"], Operator, "↑"),
+        (&["darr", "downarrow"], Operator, "↓"),
+        (&["rarr", "rightarrow", "->", "to"], Operator, "→"),
+        (&["larr", "leftarrow"], Operator, "←"),
+        (&["harr", "leftrightarrow"], Operator, "↔"),
+        (&["rArr", "Rightarrow"], Operator, "⇒"),
+        (&["lArr", "Leftarrow"], Operator, "⇐"),
+        (&["hArr", "Leftrightarrow"], Operator, "⇔"),
+        (&[", ->", "mapsto"], Operator, "↦"),
+        (&["and", "or", "if", "otherwise"], Text, ""),
+        (&["sum"], UnderOver, "∑"),
+        (&["prod"], UnderOver, "∏"),
+        (&["^^^", "bigwedge"], UnderOver, "⋀"),
+        (&["vvv", "bigvee"], UnderOver, "⋁"),
+        (&["nnn", "bigcap"], UnderOver, "⋂"),
+        (&["uuu", "bigcup"], UnderOver, "⋃"),
+        (&["lim"], UnderOver, "lim"),
+        (&["Lim"], UnderOver, "Lim"),
+        (&["min"], UnderOver, "min"),
+        (&["max"], UnderOver, "max"),
+        (
+            &[
+                "sin", "cos", "tan", "cot", "ctg", "tg", "sec", "csc", "sinh", "cosh", "tanh",
+                "coth", "arcsin", "arccos", "arctan", "log", "ln", "lg", "exp", "det", "dim",
+                "mod", "gcd", "lcm", "deg", "sgn", "lub", "glb",
+            ],
+            Function,
+            "",
+        ),
+        (&["alpha"], Ident, "α"),
+        (&["beta"], Ident, "β"),
+        (&["gamma"], Ident, "γ"),
+        (&["Gamma"], Ident, "Γ"),
+        (&["delta"], Ident, "δ"),
+        (&["Delta"], Ident, "Δ"),
+        (&["epsilon", "varepsilon"], Ident, "ε"),
+        (&["zeta"], Ident, "ζ"),
+        (&["eta"], Ident, "η"),
+        (&["theta", "vartheta"], Ident, "θ"),
+        (&["Theta"], Ident, "Θ"),
+        (&["iota"], Ident, "ι"),
+        (&["kappa"], Ident, "κ"),
+        (&["lambda"], Ident, "λ"),
+        (&["Lambda"], Ident, "Λ"),
+        (&["mu"], Ident, "μ"),
+        (&["nu"], Ident, "ν"),
+        (&["xi"], Ident, "ξ"),
+        (&["Xi"], Ident, "Ξ"),
+        (&["pi"], Ident, "π"),
+        (&["Pi"], Ident, "Π"),
+        (&["rho"], Ident, "ρ"),
+        (&["sigma"], Ident, "σ"),
+        (&["Sigma"], Ident, "Σ"),
+        (&["tau"], Ident, "τ"),
+        (&["upsilon"], Ident, "υ"),
+        (&["phi", "varphi"], Ident, "φ"),
+        (&["Phi"], Ident, "Φ"),
+        (&["chi"], Ident, "χ"),
+        (&["psi"], Ident, "ψ"),
+        (&["Psi"], Ident, "Ψ"),
+        (&["omega"], Ident, "ω"),
+        (&["Omega"], Ident, "Ω"),
+        (&["CC"], Ident, "ℂ"),
+        (&["NN"], Ident, "ℕ"),
+        (&["QQ"], Ident, "ℚ"),
+        (&["RR"], Ident, "ℝ"),
+        (&["ZZ"], Ident, "ℤ"),
+        (&["("], LeftBracket, "("),
+        (&[")"], RightBracket, ")"),
+        (&["["], LeftBracket, "["),
+        (&["]"], RightBracket, "]"),
+        (&["{"], LeftBracket, "{"),
+        (&["}"], RightBracket, "}"),
+        (&["(:", "<<", "langle"], LeftBracket, "⟨"),
+        (&[":)", ">>", "rangle"], RightBracket, "⟩"),
+        (&["{:"], LeftBracket, ""),
+        (&[":}"], RightBracket, ""),
+        (&[", "], Bar, "|"),
+        (&["sqrt"], Unary(UnaryOp::Sqrt), ""),
+        (&["hat"], Unary(UnaryOp::Over("^")), ""),
+        (&["bar", "overline"], Unary(UnaryOp::Over("‾")), ""),
+        (&["vec"], Unary(UnaryOp::Over("→")), ""),
+        (&["tilde"], Unary(UnaryOp::Over("~")), ""),
+        (&["dot"], Unary(UnaryOp::Over("˙")), ""),
+        (&["ddot"], Unary(UnaryOp::Over("¨")), ""),
+        (&["ul", "underline"], Unary(UnaryOp::Under("_")), ""),
+        (&["abs"], Unary(UnaryOp::Fence("|", "|")), ""),
+        (&["norm"], Unary(UnaryOp::Fence("‖", "‖")), ""),
+        (&["floor"], Unary(UnaryOp::Fence("⌊", "⌋")), ""),
+        (&["ceil"], Unary(UnaryOp::Fence("⌈", "⌉")), ""),
+        (
+            &["bb", "mathbf"],
+            Unary(UnaryOp::Font(0x1d400, 0x1d41a)),
+            "",
+        ),
+        (
+            &["bbb", "mathbb"],
+            Unary(UnaryOp::Font(0x1d538, 0x1d552)),
+            "",
+        ),
+        (
+            &["cc", "mathcal"],
+            Unary(UnaryOp::Font(0x1d49c, 0x1d4b6)),
+            "",
+        ),
+        (
+            &["fr", "mathfrak"],
+            Unary(UnaryOp::Font(0x1d504, 0x1d51e)),
+            "",
+        ),
+        (&["frac"], Binary(BinaryOp::Frac), ""),
+        (&["root"], Binary(BinaryOp::Root), ""),
+        (&["stackrel", "overset"], Binary(BinaryOp::Over), ""),
+        (&["underset"], Binary(BinaryOp::Under), ""),
+    ]
+};
+
+// The tokenizer looks ahead as far as the longest name in the table.
+const MAX_SYMBOL_LEN: usize = {
+    let mut max = 0;
+    let mut i = 0;
+    while i < SYMBOLS.len() {
+        let mut j = 0;
+        while j < SYMBOLS[i].0.len() {
+            if SYMBOLS[i].0[j].len() > max {
+                max = SYMBOLS[i].0[j].len();
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    max
+};
+
+fn symbol(name: &str) -> Option<(Kind, &'static str)> {
+    SYMBOLS
+        .iter()
+        .find(|(names, _, _)| names.contains(&name))
+        .map(|&(_, kind, text)| (kind, text))
+}
+
+// Control characters can't appear in XML, so they are skipped like spaces.
+fn skip_blanks(s: &str) -> &str {
+    s.trim_start_matches(|c: char| c.is_whitespace() || c.is_control())
+}
+
+fn tokenize(input: &str) -> Vec<Token<'_>> {
+    let mut res = Vec::new();
+    let mut rest = skip_blanks(input);
+    while let Some(c) = rest.chars().next() {
+        let (token, len) = if c.is_ascii_digit() {
+            let mut len = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            if rest[len..].starts_with('.')
+                && rest[len + 1..].starts_with(|c: char| c.is_ascii_digit())
+            {
+                len += 1 + rest[len + 1..]
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(rest.len() - len - 1);
+            }
+            (Some(Kind::Number), len)
+        } else if c == '"' {
+            let len = rest[1..].find('"').map_or(rest.len(), |i| i + 2);
+            res.push(Token {
+                kind: Kind::Text,
+                text: rest[1..len].trim_end_matches('"'),
+            });
+            (None, len)
+        } else if rest.starts_with("text(") {
+            let len = rest.find(')').map_or(rest.len(), |i| i + 1);
+            res.push(Token {
+                kind: Kind::Text,
+                text: rest[5..len].trim_end_matches(')'),
+            });
+            (None, len)
+        } else {
+            // The longest matching symbol wins, so that "<=" isn't read as "<" followed by "=".
+            let symbol = (1..=MAX_SYMBOL_LEN.min(rest.len()))
+                .rev()
+                .filter(|&len| rest.is_char_boundary(len))
+                .find_map(|len| symbol(&rest[..len]).map(|s| (s, len)));
+            match symbol {
+                // Words such as function names are kept as they were written.
+                Some(((kind @ (Kind::Text | Kind::Function), _), len)) => (Some(kind), len),
+                Some(((kind, text), len)) => {
+                    res.push(Token { kind, text });
+                    (None, len)
+                }
+                None if c.is_alphabetic() => (Some(Kind::Ident), c.len_utf8()),
+                None => (Some(Kind::Operator), c.len_utf8()),
+            }
+        };
+        if let Some(kind) = token {
+            res.push(Token {
+                kind,
+                text: &rest[..len],
+            });
+        }
+        rest = skip_blanks(&rest[len..]);
+    }
+    res
+}
+
+fn escape(s: &str) -> String {
+    s.replace(|c: char| c.is_control(), "")
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+struct Node {
+    mathml: String,
+    // The contents of a bracketed group, which replace the group when it is used as an argument.
+    inner: Option<String>,
+    function: bool,
+    under_over: bool,
+}
+
+impl Node {
+    fn new(mathml: String) -> Node {
+        Node {
+            mathml,
+            inner: None,
+            function: false,
+            under_over: false,
+        }
+    }
+
+    fn argument(self) -> String {
+        self.inner.unwrap_or(self.mathml)
+    }
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token<'a>> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let res = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        res
+    }
+
+    fn next_is(&self, kind: Kind, text: &str) -> bool {
+        self.peek()
+            .is_some_and(|t| t.kind == kind && t.text == text)
+    }
+
+    // E ::= I E | I / I E, stopping at a closing bracket or at a bar when inside |...|.
+    fn expression(&mut self, in_bars: bool) -> String {
+        let mut res = String::new();
+        while let Some(token) = self.peek() {
+            if token.kind == Kind::RightBracket || (in_bars && token.kind == Kind::Bar) {
+                break;
+            }
+            let node = self.intermediate(in_bars);
+            if self.next_is(Kind::Operator, "/") {
+                self.next();
+                let denominator = self.intermediate(in_bars);
+                write!(
+                    res,
+                    "<mfrac>{}{}</mfrac>",
+                    node.argument(),
+                    denominator.argument()
+                )
+                .unwrap();
+            } else {
+                res += &node.mathml;
+                if node.function {
+                    res += "<mo>\u{2061}</mo>";
+                }
+            }
+        }
+        res
+    }
+
+    // I ::= S | S_S | S^S | S_S^S
+    fn intermediate(&mut self, in_bars: bool) -> Node {
+        let base = self.simple(in_bars);
+        let sub = if self.next_is(Kind::Operator, "_") {
+            self.next();
+            Some(self.simple(in_bars).argument())
+        } else {
+            None
+        };
+        let sup = if self.next_is(Kind::Operator, "^") {
+            self.next();
+            Some(self.simple(in_bars).argument())
+        } else {
+            None
+        };
+        let (under, over, both) = if base.under_over {
+            ("munder", "mover", "munderover")
+        } else {
+            ("msub", "msup", "msubsup")
+        };
+        let mathml = match (sub, sup) {
+            (None, None) => return base,
+            (Some(sub), None) => format!("<{0}>{1}{2}</{0}>", under, base.mathml, sub),
+            (None, Some(sup)) => format!("<{0}>{1}{2}</{0}>", over, base.mathml, sup),
+            (Some(sub), Some(sup)) => {
+                format!("<{0}>{1}{2}{3}</{0}>", both, base.mathml, sub, sup)
+            }
+        };
+        Node {
+            function: base.function,
+            ..Node::new(mathml)
+        }
+    }
+
+    // S ::= v | l E r | u S | b S S
+    fn simple(&mut self, in_bars: bool) -> Node {
+        // A missing argument, as in "x^" or "(x_)", is left empty.
+        let Some(token) = self
+            .peek()
+            .filter(|t| !(t.kind == Kind::RightBracket || (in_bars && t.kind == Kind::Bar)))
+            .cloned()
+        else {
+            return Node::new("<mrow></mrow>".to_string());
+        };
+        self.position += 1;
+        let text = escape(token.text);
+        match token.kind {
+            Kind::Number => Node::new(format!("<mn>{}</mn>", text)),
+            Kind::Ident => Node::new(format!("<mi>{}</mi>", text)),
+            Kind::Operator | Kind::RightBracket => Node::new(format!("<mo>{}</mo>", text)),
+            Kind::Text => Node::new(format!("<mtext>{}</mtext>", text)),
+            Kind::Function => Node {
+                function: true,
+                ..Node::new(format!("<mi>{}</mi>", text))
+            },
+            Kind::UnderOver => {
+                let tag = if token.text.chars().all(char::is_alphabetic) {
+                    "mi"
+                } else {
+                    "mo"
+                };
+                Node {
+                    under_over: true,
+                    function: tag == "mi",
+                    ..Node::new(format!("<{0}>{1}</{0}>", tag, text))
+                }
+            }
+            Kind::LeftBracket => {
+                let inner = self.expression(false);
+                let close = match self.peek() {
+                    Some(t) if t.kind == Kind::RightBracket => self.next().unwrap().text,
+                    _ => "",
+                };
+                let fence = |s: &str| {
+                    if s.is_empty() {
+                        String::new()
+                    } else {
+                        format!("<mo>{}</mo>", escape(s))
+                    }
+                };
+                Node {
+                    inner: Some(format!("<mrow>{}</mrow>", inner)),
+                    ..Node::new(format!(
+                        "<mrow>{}{}{}</mrow>",
+                        fence(token.text),
+                        inner,
+                        fence(close)
+                    ))
+                }
+            }
+            Kind::Bar => {
+                let inner = self.expression(true);
+                let close = if self.peek().is_some_and(|t| t.kind == Kind::Bar) {
+                    self.next();
+                    "<mo>|</mo>"
+                } else {
+                    ""
+                };
+                Node::new(format!("<mrow><mo>|</mo>{}{}</mrow>", inner, close))
+            }
+            Kind::Unary(unary) => {
+                let argument = self.simple(in_bars).argument();
+                Node::new(match unary {
+                    UnaryOp::Sqrt => format!("<msqrt>{}</msqrt>", argument),
+                    UnaryOp::Over(accent) => {
+                        format!("<mover>{}<mo>{}</mo></mover>", argument, accent)
+                    }
+                    UnaryOp::Under(accent) => {
+                        format!("<munder>{}<mo>{}</mo></munder>", argument, accent)
+                    }
+                    UnaryOp::Fence(open, close) => {
+                        format!(
+                            "<mrow><mo>{}</mo>{}<mo>{}</mo></mrow>",
+                            open, argument, close
+                        )
+                    }
+                    UnaryOp::Font(capital, small) => styled(&argument, capital, small),
+                })
+            }
+            Kind::Binary(binary) => {
+                let first = self.simple(in_bars).argument();
+                let second = self.simple(in_bars).argument();
+                Node::new(match binary {
+                    BinaryOp::Frac => format!("<mfrac>{}{}</mfrac>", first, second),
+                    BinaryOp::Root => format!("<mroot>{}{}</mroot>", second, first),
+                    BinaryOp::Over => format!("<mover>{}{}</mover>", second, first),
+                    BinaryOp::Under => format!("<munder>{}{}</munder>", second, first),
+                })
+            }
+        }
+    }
+}
+
+// Replaces Latin letters with their mathematical alphanumeric counterparts, such as bold or double-struck.
+fn styled(mathml: &str, capital: u32, small: u32) -> String {
+    let mut res = String::with_capacity(mathml.len());
+    let mut in_tag = false;
+    let mut in_entity = false;
+    for c in mathml.chars() {
+        in_tag = (in_tag || c == '<') && c != '>';
+        in_entity = (in_entity || c == '&') && c != ';';
+        let markup = in_tag || in_entity;
+        let code = match c {
+            'A'..='Z' if !markup => char::from_u32(capital + (c as u32 - 'A' as u32)),
+            'a'..='z' if !markup => char::from_u32(small + (c as u32 - 'a' as u32)),
+            _ => None,
+        }
+        // Some letters, such as double-struck R, were encoded before the block and left holes in it.
+        .map(|c| symbol_tables::letterlike(c).unwrap_or(c));
+        res.push(code.unwrap_or(c));
+    }
+    res
+}
+
+pub fn to_mathml(asciimath: &str) -> String {
+    let mut parser = Parser {
+        tokens: tokenize(asciimath),
+        position: 0,
+    };
+    let mut res = String::from("<math>");
+    while parser.peek().is_some() {
+        res += &parser.expression(false);
+        // An unmatched closing bracket is kept as an ordinary operator, an invisible one is dropped.
+        if let Some(token) = parser.next().filter(|t| !t.text.is_empty()) {
+            write!(res, "<mo>{}</mo>", escape(token.text)).unwrap();
+        }
+    }
+    res += "</math>";
+    res
+}
+
+pub fn render(asciimath: &str) -> Result<BrailleString, RenderError> {
+    crate::render(&to_mathml(asciimath))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn longest_symbol() {
+        assert_eq!(MAX_SYMBOL_LEN, "leftrightarrow".len());
+    }
+
+    #[test]
+    fn multi_letter_symbols() {
+        assert_eq!(
+            to_mathml("a rightarrow b"),
+            "<math><mi>a</mi><mo>→</mo><mi>b</mi></math>"
+        );
+        assert_eq!(
+            to_mathml("a leftrightarrow b"),
+            "<math><mi>a</mi><mo>↔</mo><mi>b</mi></math>"
+        );
+        assert_eq!(
+            to_mathml("A Leftrightarrow B"),
+            "<math><mi>A</mi><mo>⇔</mo><mi>B</mi></math>"
+        );
+        assert_eq!(to_mathml("varepsilon"), "<math><mi>ε</mi></math>");
+        assert_eq!(
+            to_mathml("A subseteq B"),
+            "<math><mi>A</mi><mo>⊆</mo><mi>B</mi></math>"
+        );
+        assert_eq!(to_mathml("therefore"), "<math><mo>∴</mo></math>");
+    }
+
+    fn mathml(asciimath: &str) -> String {
+        let res = to_mathml(asciimath);
+        res.strip_prefix("<math>")
+            .and_then(|s| s.strip_suffix("</math>"))
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn fractions() {
+        assert_eq!(mathml("1/2"), "<mfrac><mn>1</mn><mn>2</mn></mfrac>");
+        assert_eq!(mathml("frac 1 2"), "<mfrac><mn>1</mn><mn>2</mn></mfrac>");
+        // The brackets around an argument only group it.
+        assert_eq!(
+            mathml("(a+b)/c"),
+            "<mfrac><mrow><mi>a</mi><mo>+</mo><mi>b</mi></mrow><mi>c</mi></mfrac>"
+        );
+    }
+
+    #[test]
+    fn roots() {
+        assert_eq!(mathml("sqrt x"), "<msqrt><mi>x</mi></msqrt>");
+        assert_eq!(mathml("root 3 x"), "<mroot><mi>x</mi><mn>3</mn></mroot>");
+        assert_eq!(
+            mathml("root(3)(x)"),
+            "<mroot><mrow><mi>x</mi></mrow><mrow><mn>3</mn></mrow></mroot>"
+        );
+    }
+
+    #[test]
+    fn scripts() {
+        assert_eq!(
+            mathml("x_1^2"),
+            "<msubsup><mi>x</mi><mn>1</mn><mn>2</mn></msubsup>"
+        );
+        assert_eq!(
+            mathml("sum_(i=1)^n i"),
+            "<munderover><mo>∑</mo><mrow><mi>i</mi><mo>=</mo><mn>1</mn></mrow><mi>n</mi>\
+            </munderover><mi>i</mi>"
+        );
+        assert_eq!(
+            mathml("lim_(x->0) f"),
+            "<munder><mi>lim</mi><mrow><mi>x</mi><mo>→</mo><mn>0</mn></mrow></munder>\
+            <mo>\u{2061}</mo><mi>f</mi>"
+        );
+    }
+
+    #[test]
+    fn brackets() {
+        assert_eq!(mathml("(x)"), "<mrow><mo>(</mo><mi>x</mi><mo>)</mo></mrow>");
+        assert_eq!(
+            mathml("[a,b]"),
+            "<mrow><mo>[</mo><mi>a</mi><mo>,</mo><mi>b</mi><mo>]</mo></mrow>"
+        );
+        assert_eq!(mathml("{:x:}"), "<mrow><mi>x</mi></mrow>");
+    }
+
+    #[test]
+    fn readme_example() {
+        assert_eq!(
+            mathml("sqrt(x^2+1)/(2a)"),
+            "<mfrac><msqrt><mrow><msup><mi>x</mi><mn>2</mn></msup><mo>+</mo><mn>1</mn></mrow>\
+            </msqrt><mrow><mn>2</mn><mi>a</mi></mrow></mfrac>"
+        );
+    }
+
+    #[test]
+    fn unbalanced_and_empty() {
+        assert_eq!(mathml(""), "");
+        assert_eq!(mathml("   "), "");
+        assert_eq!(mathml("(x"), "<mrow><mo>(</mo><mi>x</mi></mrow>");
+        assert_eq!(mathml("x)"), "<mi>x</mi><mo>)</mo>");
+        assert_eq!(mathml(":}"), "");
+        assert_eq!(mathml("x^"), "<msup><mi>x</mi><mrow></mrow></msup>");
+    }
+
+    #[test]
+    fn control_characters() {
+        assert_eq!(mathml("\0"), "");
+        assert_eq!(mathml("a\u{7}b"), "<mi>a</mi><mi>b</mi>");
+        assert_eq!(mathml("\"a\0b\""), "<mtext>ab</mtext>");
+        for input in ["\0", "x\u{1b}[0m", "text(\u{8})"] {
+            assert!(render(input).is_ok(), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn letterlike_fonts() {
+        assert_eq!(to_mathml("bbb R"), "<math><mi>ℝ</mi></math>");
+        assert_eq!(to_mathml("cc L"), "<math><mi>ℒ</mi></math>");
+        assert_eq!(to_mathml("fr Z"), "<math><mi>ℨ</mi></math>");
+        assert_eq!(to_mathml("bbb A"), "<math><mi>𝔸</mi></math>");
+        assert_eq!(
+            render("bbb R").unwrap().to_dots(),
+            render("RR").unwrap().to_dots()
+        );
+    }
+}
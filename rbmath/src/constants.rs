@@ -6,6 +6,10 @@ use brl::BraillePattern::*;
 // Universal signs:
 pub const NUMBER_SIGN: BraillePattern = Braille3456;
 pub const PLACEHOLDER: BraillePattern = Braille123456;
+pub const DECIMAL_MARK: BraillePattern = Braille2;
+pub const GROUP_SEPARATOR: BraillePattern = Braille3;
+// Separates a number from a following letter that would read as a digit.
+pub const LETTER_SEPARATOR: BraillePattern = Braille56;
 
 // Script and font prefixes:
 pub const SMALL_GREEK_PREFIX: BraillePattern = Braille56;
@@ -78,6 +82,57 @@ pub const GREEK_ALPHABET: [BraillePattern; 25] = [
     Braille2456,
 ];
 
+// Russian alphabet from а to я, without ё:
+pub const CYRILLIC_ALPHABET: [BraillePattern; 32] = [
+    Braille1,
+    Braille12,
+    Braille2456,
+    Braille1245,
+    Braille145,
+    Braille15,
+    Braille245,
+    Braille1356,
+    Braille24,
+    Braille12346,
+    Braille13,
+    Braille123,
+    Braille134,
+    Braille1345,
+    Braille135,
+    Braille1234,
+    Braille1235,
+    Braille234,
+    Braille2345,
+    Braille136,
+    Braille124,
+    Braille125,
+    Braille14,
+    Braille12345,
+    Braille156,
+    Braille1346,
+    Braille12356,
+    Braille2346,
+    Braille23456,
+    Braille246,
+    Braille1256,
+    Braille1246,
+];
+pub const CYRILLIC_YO: BraillePattern = Braille16;
+
+// Digits written after the number sign, from 0 to 9:
+pub const DIGITS: [BraillePattern; 10] = [
+    Braille245,
+    Braille1,
+    Braille12,
+    Braille14,
+    Braille145,
+    Braille15,
+    Braille124,
+    Braille1245,
+    Braille125,
+    Braille24,
+];
+
 // Unicode ranges for various combinations of font and script from the Mathematical Alphanumeric Symbols block:
 pub const CAPITAL_LATIN_RANGE: RangeInclusive<u32> = 0x41..=0x5a;
 pub const CAPITAL_LATIN_BOLD_RANGE: RangeInclusive<u32> = 0x1d400..=0x1d419;
@@ -87,7 +142,7 @@ pub const CAPITAL_FRAKTUR_RANGE: RangeInclusive<u32> = 0x1d504..=0x1d51d;
 pub const CAPITAL_LATIN_DOUBLESTRUCK_RANGE: RangeInclusive<u32> = 0x1d538..=0x1d551;
 
 pub const SMALL_LATIN_RANGE: RangeInclusive<u32> = 0x61..=0x7a;
-pub const SMALL_LATIN_BOLD_RANGE: RangeInclusive<u32> = 0x1d41a..=0x1d435;
+pub const SMALL_LATIN_BOLD_RANGE: RangeInclusive<u32> = 0x1d41a..=0x1d433;
 pub const SMALL_LATIN_CALLIGRAPHIC_RANGE: RangeInclusive<u32> = 0x1d4b6..=0x1d4cf;
 pub const SMALL_LATIN_BOLD_CALLIGRAPHIC_RANGE: RangeInclusive<u32> = 0x1d4ea..=0x1d503;
 pub const SMALL_FRAKTUR_RANGE: RangeInclusive<u32> = 0x1d51e..=0x1d537;
@@ -97,3 +152,6 @@ pub const CAPITAL_GREEK_RANGE: RangeInclusive<u32> = 0x391..=0x3a9;
 pub const CAPITAL_GREEK_BOLD_RANGE: RangeInclusive<u32> = 0x1d6a8..=0x1d6c0;
 pub const SMALL_GREEK_RANGE: RangeInclusive<u32> = 0x3b1..=0x3c9;
 pub const SMALL_GREEK_BOLD_RANGE: RangeInclusive<u32> = 0x1d6c2..=0x1d6da;
+
+pub const CAPITAL_CYRILLIC_RANGE: RangeInclusive<u32> = 0x410..=0x42f;
+pub const SMALL_CYRILLIC_RANGE: RangeInclusive<u32> = 0x430..=0x44f;
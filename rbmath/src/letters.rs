@@ -8,13 +8,13 @@ use crate::constants::{
     LATIN_ALPHABET, SMALL_FRAKTUR_PREFIX, SMALL_GREEK_PREFIX, SMALL_LATIN_PREFIX,
 };
 
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum Script {
     Latin,
     Greek,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum Font {
     Normal,
     Bold,
@@ -171,3 +171,66 @@ impl Letter {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify(c: char) -> (usize, Script, Font, bool) {
+        let Letter(index, cls) = Classifier::classify(c).unwrap();
+        (index, cls.script, cls.font, cls.capital)
+    }
+
+    #[test]
+    fn latin() {
+        assert_eq!(classify('a'), (0, Script::Latin, Font::Normal, false));
+        assert_eq!(classify('Z'), (25, Script::Latin, Font::Normal, true));
+        assert_eq!(classify('𝐛'), (1, Script::Latin, Font::Bold, false));
+        assert_eq!(classify('𝒞'), (2, Script::Latin, Font::Calligraphic, true));
+        assert_eq!(
+            classify('𝓭'),
+            (3, Script::Latin, Font::BoldCalligraphic, false)
+        );
+        assert_eq!(
+            classify('𝕣'),
+            (17, Script::Latin, Font::Doublestruck, false)
+        );
+        assert_eq!(classify('𝔤'), (6, Script::Latin, Font::Fraktur, false));
+    }
+
+    #[test]
+    fn small_bold_latin_ends_at_z() {
+        assert_eq!(classify('𝐳'), (25, Script::Latin, Font::Bold, false));
+        // Italic capitals follow, they aren't bold letters.
+        assert!(Classifier::classify('\u{1d434}').is_none_or(|l| l.1.font != Font::Bold));
+    }
+
+    #[test]
+    fn greek() {
+        assert_eq!(classify('α'), (0, Script::Greek, Font::Normal, false));
+        assert_eq!(classify('ω'), (24, Script::Greek, Font::Normal, false));
+        assert_eq!(classify('Δ'), (3, Script::Greek, Font::Normal, true));
+        assert_eq!(classify('𝛃'), (1, Script::Greek, Font::Bold, false));
+        assert_eq!(
+            Classifier::classify('α').unwrap().get_rendering().to_dots(),
+            GREEK_ALPHABET[0].to_dots()
+        );
+    }
+
+    #[test]
+    fn not_letters() {
+        for c in ['1', '+', ' ', 'ж', '∑', '\u{2061}'] {
+            assert!(Classifier::classify(c).is_none(), "{:?}", c);
+        }
+    }
+
+    #[test]
+    fn prefixes() {
+        let small = Classifier::classify('x').unwrap().1.get_prefix();
+        assert_eq!(small.to_dots(), "6");
+        let capital = Classifier::classify('X').unwrap().1.get_prefix();
+        assert_eq!(capital.to_dots(), "46");
+        let fraktur = Classifier::classify('𝔤').unwrap().1.get_prefix();
+        assert_eq!(fraktur.to_dots(), "5");
+    }
+}
@@ -1,21 +1,207 @@
+pub mod asciimath;
 pub(crate) mod constants;
 pub(crate) mod letters;
+pub mod liblouis;
+pub mod literary;
 pub(crate) mod math_element;
+pub mod navigation;
+pub mod omml;
 pub(crate) mod renderers;
+pub mod speech;
 pub(crate) mod symbol_tables;
 pub(crate) mod traits;
+pub mod xhtml;
+
+use std::{error::Error, fmt::Display, ops::Range, str::FromStr};
 
 use brl::BrailleString;
 use math_element::MathElement;
 use renderers::BasicRenderer;
 use traits::Renderer;
 
-pub fn render(ml: &str) -> BrailleString {
-    let ml: String = ml.chars().filter_map(symbol_tables::normalize).collect();
-    let document = roxmltree::Document::parse(&ml).unwrap(); // TODO: Error type.
-    let mut r = BasicRenderer::new();
-    if let Some(first) = MathElement::from_xml(document.root_element()) {
-        first.render(&mut r);
+pub use symbol_tables::{clear_custom_functions, clear_custom_symbols, register_function};
+
+#[derive(Debug)]
+pub enum RenderError {
+    Xml(roxmltree::Error),
+    NotMathML(String),
+}
+
+impl Display for RenderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RenderError::Xml(e) => write!(f, "invalid XML: {}", e),
+            RenderError::NotMathML(tag) => write!(f, "unexpected root element {}", tag),
+        }
+    }
+}
+
+impl Error for RenderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RenderError::Xml(e) => Some(e),
+            RenderError::NotMathML(_) => None,
+        }
+    }
+}
+
+impl From<roxmltree::Error> for RenderError {
+    fn from(value: roxmltree::Error) -> Self {
+        RenderError::Xml(value)
+    }
+}
+
+// Character that separates the fractional part of a number in the source.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum DecimalMark {
+    // Both commas and points are decimal marks, as in Russian texts.
+    #[default]
+    Comma,
+    // Commas separate digit groups, as in English texts.
+    Point,
+}
+
+impl FromStr for DecimalMark {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "comma" => Ok(DecimalMark::Comma),
+            "point" => Ok(DecimalMark::Point),
+            _ => Err(format!(
+                "unknown decimal mark {}, expected comma or point",
+                s
+            )),
+        }
+    }
+}
+
+// Whether formulas of substances and reactions follow the rules of chemistry.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum Chemistry {
+    #[default]
+    Off,
+    // Formulas made with mhchem are recognized by the counts and charges after element symbols.
+    Auto,
+    On,
+}
+
+impl FromStr for Chemistry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Chemistry::Off),
+            "auto" => Ok(Chemistry::Auto),
+            "on" => Ok(Chemistry::On),
+            _ => Err(format!(
+                "unknown chemistry mode {}, expected off, auto or on",
+                s
+            )),
+        }
+    }
+}
+
+impl Chemistry {
+    pub(crate) fn applies(self, element: &MathElement) -> bool {
+        match self {
+            Chemistry::Off => false,
+            Chemistry::Auto => element.is_chemistry(),
+            Chemistry::On => true,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct RenderOptions {
+    pub decimal_mark: DecimalMark,
+    pub chemistry: Chemistry,
+}
+
+// Maps a range of braille cells in the output to the byte range of the MathML element it was produced from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub cells: Range<usize>,
+    pub source: Range<usize>,
+}
+
+pub fn render(ml: &str) -> Result<BrailleString, RenderError> {
+    render_with_spans(ml).map(|(res, _)| res)
+}
+
+pub fn render_with_options(ml: &str, options: RenderOptions) -> Result<BrailleString, RenderError> {
+    render_source(ml, options).map(|(res, _)| res)
+}
+
+// Normalization may change the byte length of characters, so remember where each of them came from.
+pub(crate) fn normalize_source(ml: &str) -> (String, Vec<usize>) {
+    let mut normalized = String::with_capacity(ml.len());
+    let mut offsets = Vec::with_capacity(ml.len() + 1);
+    for (i, c) in ml.char_indices() {
+        if let Some(n) = symbol_tables::normalize(c) {
+            normalized.push(n);
+            offsets.resize(normalized.len(), i);
+        }
+    }
+    offsets.push(ml.len());
+    (normalized, offsets)
+}
+
+pub(crate) fn render_element(
+    element: &MathElement,
+    options: RenderOptions,
+) -> (BrailleString, Vec<Span>) {
+    // The renderer only sees whether the rules of chemistry apply.
+    let options = RenderOptions {
+        chemistry: if options.chemistry.applies(element) {
+            Chemistry::On
+        } else {
+            Chemistry::Off
+        },
+        ..options
+    };
+    let mut r = BasicRenderer::new(options);
+    element.render(&mut r);
+    let spans = r.take_spans();
+    (r.finish(), spans)
+}
+
+pub(crate) fn render_node(
+    node: roxmltree::Node,
+    options: RenderOptions,
+) -> (BrailleString, Vec<Span>) {
+    match MathElement::from_xml(node) {
+        Some(first) => render_element(&first, options),
+        None => (BrailleString::new(), Vec::new()),
+    }
+}
+
+// Spans are sorted by their position in the source, so parents come before their children.
+pub fn render_with_spans(ml: &str) -> Result<(BrailleString, Vec<Span>), RenderError> {
+    render_source(ml, RenderOptions::default())
+}
+
+pub fn render_with_spans_and_options(
+    ml: &str,
+    options: RenderOptions,
+) -> Result<(BrailleString, Vec<Span>), RenderError> {
+    render_source(ml, options)
+}
+
+fn render_source(
+    ml: &str,
+    options: RenderOptions,
+) -> Result<(BrailleString, Vec<Span>), RenderError> {
+    let (normalized, offsets) = normalize_source(ml);
+    let document = roxmltree::Document::parse(&normalized)?;
+    let root = document.root_element();
+    if root.tag_name().name() != "math" {
+        return Err(RenderError::NotMathML(root.tag_name().name().to_string()));
+    }
+    let (res, mut spans) = render_node(root, options);
+    for span in &mut spans {
+        span.source = offsets[span.source.start]..offsets[span.source.end];
     }
-    r.finish()
+    spans.sort_by_key(|s| (s.source.start, std::cmp::Reverse(s.source.end)));
+    Ok((res, spans))
 }
@@ -0,0 +1,303 @@
+use std::{error::Error, fmt::Display, fmt::Write};
+
+use brl::{BraillePattern, BrailleString};
+
+use crate::{constants::NUMBER_SIGN, letters::Classifier, symbol_tables};
+
+// Characters outside of these ranges are never produced by the renderer, so there's no point in exporting them.
+const EXPORTED_RANGES: [std::ops::Range<u32>; 2] = [0x20..0x3000, 0x1d400..0x1d800];
+
+#[derive(Debug)]
+pub enum TableError {
+    Syntax { line: usize, message: String },
+    InvalidDots { line: usize, dots: String },
+}
+
+impl Display for TableError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TableError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            TableError::InvalidDots { line, dots } => {
+                write!(f, "line {}: invalid dot pattern {}", line, dots)
+            }
+        }
+    }
+}
+
+impl Error for TableError {}
+
+fn escape_chars(c: char) -> String {
+    match c {
+        ' ' => "\\s".to_string(),
+        '\\' => "\\\\".to_string(),
+        c if c.is_ascii_graphic() => c.to_string(),
+        c if (c as u32) <= 0xffff => format!("\\x{:04x}", c as u32),
+        c => format!("\\y{:05x}", c as u32),
+    }
+}
+
+fn format_dots(s: &BrailleString) -> String {
+    s.to_dots().replace(' ', "-")
+}
+
+fn exported_chars() -> impl Iterator<Item = char> {
+    EXPORTED_RANGES
+        .into_iter()
+        .flatten()
+        .filter_map(char::from_u32)
+}
+
+pub fn export_table() -> String {
+    let mut res = String::new();
+    res += "# Generated by rbmath. Do not edit.\n\n";
+    writeln!(res, "numsign {}", format_dots(&NUMBER_SIGN.into())).unwrap();
+
+    res += "\n# Letters\n";
+    for c in exported_chars() {
+        let Some(n) = symbol_tables::normalize(c) else {
+            continue;
+        };
+        if let Some(l) = Classifier::classify(n) {
+            let mut dots = l.1.get_prefix();
+            dots += l.get_rendering();
+            writeln!(res, "letter {} {}", escape_chars(c), format_dots(&dots)).unwrap();
+        }
+    }
+
+    res += "\n# Functions\n";
+    for &f in symbol_tables::SPECIAL_FUNCTIONS {
+        if let Some(dots) = symbol_tables::builtin_function(f) {
+            writeln!(res, "word {} {}", f, format_dots(&dots)).unwrap();
+        }
+    }
+
+    res += "\n# Symbols\n";
+    for c in exported_chars() {
+        let Some(n) = symbol_tables::normalize(c) else {
+            continue;
+        };
+        if let Some(dots) = symbol_tables::builtin_symbol(n) {
+            writeln!(res, "math {} {}", escape_chars(c), format_dots(&dots)).unwrap();
+        }
+    }
+    res
+}
+
+fn unescape_chars(s: &str, line: usize) -> Result<String, TableError> {
+    let syntax = |message: &str| TableError::Syntax {
+        line,
+        message: message.to_string(),
+    };
+    let mut res = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            res.push(c);
+            continue;
+        }
+        let hex_len = match chars.next() {
+            Some('s') => {
+                res.push(' ');
+                continue;
+            }
+            Some('t') => {
+                res.push('\t');
+                continue;
+            }
+            Some('\\') => {
+                res.push('\\');
+                continue;
+            }
+            Some('x') => 4,
+            Some('y') => 5,
+            Some('z') => 8,
+            _ => return Err(syntax("unknown escape sequence")),
+        };
+        let hex: String = chars.by_ref().take(hex_len).collect();
+        let code = u32::from_str_radix(&hex, 16).map_err(|_| syntax("invalid hex escape"))?;
+        res.push(char::from_u32(code).ok_or_else(|| syntax("invalid character code"))?);
+    }
+    Ok(res)
+}
+
+fn parse_dots(s: &str, line: usize) -> Result<BrailleString, TableError> {
+    let invalid = || TableError::InvalidDots {
+        line,
+        dots: s.to_string(),
+    };
+    let mut res = BrailleString::new();
+    for cell in s.split('-') {
+        if cell.is_empty() {
+            return Err(invalid());
+        }
+        let mut pattern = BraillePattern::BrailleEmpty;
+        for d in cell.chars() {
+            match d.to_digit(10) {
+                Some(0) if cell.len() == 1 => {}
+                Some(d @ 1..=6) => pattern += BraillePattern::from_dots(d as usize),
+                _ => return Err(invalid()),
+            }
+        }
+        res += pattern;
+    }
+    Ok(res)
+}
+
+// Symbols and function names read from a liblouis table, in the order of the table.
+#[derive(Default)]
+pub struct Table {
+    pub symbols: Vec<(char, BrailleString)>,
+    pub functions: Vec<(String, BrailleString)>,
+}
+
+impl Table {
+    pub fn len(&self) -> usize {
+        self.symbols.len() + self.functions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Adds the entries to the custom symbols and functions used by every rendering.
+    pub fn register(self) {
+        for (c, dots) in self.symbols {
+            symbol_tables::register_symbol(c, dots);
+        }
+        for (name, dots) in self.functions {
+            symbol_tables::register_function(&name, dots);
+        }
+    }
+}
+
+// Only the math and sign opcodes and function names given by word are read, everything else is skipped.
+pub fn parse_table(table: &str) -> Result<Table, TableError> {
+    let mut res = Table::default();
+    for (i, line) in table.lines().enumerate() {
+        let line_no = i + 1;
+        let mut fields = line
+            .split_whitespace()
+            .skip_while(|f| *f == "noback" || *f == "nofor");
+        let Some(opcode) = fields.next() else {
+            continue;
+        };
+        if opcode.starts_with('#') || !matches!(opcode, "math" | "sign" | "word") {
+            continue;
+        }
+        let (Some(chars), Some(dots)) = (fields.next(), fields.next()) else {
+            return Err(TableError::Syntax {
+                line: line_no,
+                message: format!("{} requires characters and dots", opcode),
+            });
+        };
+        let chars = unescape_chars(chars, line_no)?;
+        let dots = parse_dots(dots, line_no)?;
+        let mut it = chars.chars();
+        if opcode == "word" {
+            res.functions.push((chars, dots));
+        } else if let (Some(c), None) = (it.next(), it.next()) {
+            res.symbols.push((c, dots));
+        } else {
+            log::warn!(
+                "Skipping multi-character {} entry {} on line {}",
+                opcode,
+                chars,
+                line_no
+            );
+        }
+    }
+    Ok(res)
+}
+
+// Parses the table and registers its entries for every rendering. Returns the number of imported symbols.
+pub fn import_table(table: &str) -> Result<usize, TableError> {
+    let table = parse_table(table)?;
+    let count = table.len();
+    table.register();
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escaping() {
+        for (c, escaped) in [
+            ('a', "a"),
+            (' ', "\\s"),
+            ('\\', "\\\\"),
+            ('∑', "\\x2211"),
+            ('𝐚', "\\y1d41a"),
+        ] {
+            assert_eq!(escape_chars(c), escaped);
+            assert_eq!(unescape_chars(escaped, 1).unwrap(), c.to_string());
+        }
+        assert_eq!(unescape_chars("\\t\\z0001d400", 1).unwrap(), "\t𝐀");
+        assert!(matches!(
+            unescape_chars("\\q", 3),
+            Err(TableError::Syntax { line: 3, .. })
+        ));
+        assert!(unescape_chars("\\x12g4", 1).is_err());
+        assert!(unescape_chars("\\xd800", 1).is_err());
+    }
+
+    #[test]
+    fn dots() {
+        assert_eq!(parse_dots("1-12-3456", 1).unwrap().to_dots(), "1 12 3456");
+        assert_eq!(parse_dots("0", 1).unwrap().to_dots(), "0");
+        assert_eq!(format_dots(&parse_dots("146-0-1", 1).unwrap()), "146-0-1");
+        for invalid in ["", "1--2", "7", "10", "1a"] {
+            assert!(
+                matches!(
+                    parse_dots(invalid, 2),
+                    Err(TableError::InvalidDots { line: 2, .. })
+                ),
+                "{:?}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn parse_skips_other_opcodes() {
+        let table = "# comment\ninclude other.ctb\nletter a 1\nnoback sign \\xe000 146-0\nword rot 1246-1235\n";
+        let table = parse_table(table).unwrap();
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.symbols[0].0, '\u{e000}');
+        assert_eq!(format_dots(&table.symbols[0].1), "146-0");
+        assert_eq!(table.functions[0].0, "rot");
+        assert!(matches!(
+            parse_table("math +\n"),
+            Err(TableError::Syntax { line: 1, .. })
+        ));
+    }
+
+    // The exported table reads back as the braille every symbol and function already has.
+    #[test]
+    fn export_import_round_trip() {
+        let exported = export_table();
+        let entries = exported
+            .lines()
+            .filter(|l| l.starts_with("math ") || l.starts_with("word "))
+            .count();
+        assert!(exported.contains("\nmath + 235\n"));
+        let table = parse_table(&exported).unwrap();
+        assert_eq!(table.len(), entries);
+        for (c, dots) in &table.symbols {
+            let n = symbol_tables::normalize(*c).unwrap();
+            assert_eq!(
+                dots.to_dots(),
+                symbol_tables::builtin_symbol(n).unwrap().to_dots(),
+                "{}",
+                c
+            );
+        }
+        for (f, dots) in &table.functions {
+            assert_eq!(
+                dots.to_dots(),
+                symbol_tables::builtin_function(f).unwrap().to_dots()
+            );
+        }
+    }
+}
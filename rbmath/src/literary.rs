@@ -0,0 +1,144 @@
+use brl::{braille, BraillePattern, BrailleString};
+
+use crate::{
+    constants::{
+        CAPITAL_CYRILLIC_RANGE, CYRILLIC_ALPHABET, CYRILLIC_YO, DIGITS, LETTER_SEPARATOR,
+        NUMBER_SIGN, PLACEHOLDER, SMALL_CYRILLIC_RANGE,
+    },
+    letters::Classifier,
+    symbol_tables,
+};
+
+pub fn cyrillic_letter(c: char) -> Option<BraillePattern> {
+    let code = c as u32;
+    if SMALL_CYRILLIC_RANGE.contains(&code) {
+        Some(CYRILLIC_ALPHABET[(code - SMALL_CYRILLIC_RANGE.start()) as usize])
+    } else if CAPITAL_CYRILLIC_RANGE.contains(&code) {
+        Some(CYRILLIC_ALPHABET[(code - CAPITAL_CYRILLIC_RANGE.start()) as usize])
+    } else if c == 'ё' || c == 'Ё' {
+        Some(CYRILLIC_YO)
+    } else {
+        None
+    }
+}
+
+fn punctuation(c: char) -> Option<BrailleString> {
+    Some(match c {
+        '.' => braille!(256),
+        ',' => braille!(2),
+        '?' => braille!(26),
+        '!' => braille!(235),
+        ':' => braille!(25),
+        ';' => braille!(23),
+        '-' | '‐' => braille!(36),
+        '–' | '—' => braille!(36, 36),
+        '(' => braille!(126),
+        ')' => braille!(345),
+        '«' | '„' | '“' => braille!(236),
+        '»' | '”' => braille!(356),
+        '"' => braille!(236),
+        '\'' | '’' => braille!(3),
+        '/' => braille!(34),
+        '%' => braille!(3456, 245, 356),
+        '*' => braille!(35),
+        _ => return None,
+    })
+}
+
+// Literary (non-mathematical) Russian braille for the text surrounding formulas.
+// Capital Cyrillic letters are not marked, as is customary in Russian braille.
+pub fn render_text(text: &str) -> BrailleString {
+    let mut res = BrailleString::new();
+    let mut last_classifier: Option<Classifier> = None;
+    let mut in_number = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                chars.next();
+            }
+            res += BraillePattern::BrailleEmpty;
+            last_classifier = None;
+            in_number = false;
+        } else if let Some(d) = c.to_digit(10) {
+            if !in_number {
+                res += NUMBER_SIGN;
+                in_number = true;
+            }
+            res += DIGITS[d as usize];
+            last_classifier = None;
+        } else if (c == ',' || c == '.')
+            && in_number
+            && chars.peek().is_some_and(|c| c.is_ascii_digit())
+        {
+            res += BraillePattern::Braille2;
+        } else if let Some(l) = cyrillic_letter(c) {
+            if in_number && DIGITS.contains(&l) {
+                res += LETTER_SEPARATOR;
+            }
+            res += l;
+            last_classifier = None;
+            in_number = false;
+        } else if let Some(l) = Classifier::classify(c) {
+            if last_classifier != Some(l.1) {
+                res += &l.1.get_prefix();
+            }
+            res += l.get_rendering();
+            last_classifier = Some(l.1);
+            in_number = false;
+        } else {
+            res += &punctuation(c)
+                .or_else(|| symbol_tables::builtin_symbol(c))
+                .unwrap_or_else(|| {
+                    log::warn!("Unrecognized character in text: {}", c);
+                    PLACEHOLDER.into()
+                });
+            last_classifier = None;
+            in_number = false;
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dots(text: &str) -> String {
+        render_text(text).to_dots()
+    }
+
+    #[test]
+    fn cyrillic() {
+        assert_eq!(dots("Мир"), "134 24 1235");
+        assert_eq!(dots("ёж"), "16 245");
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(dots("12"), "3456 1 12");
+        assert_eq!(dots("3,5"), "3456 14 2 15");
+        // A comma not followed by a digit ends the number.
+        assert_eq!(dots("3, 5"), "3456 14 2 0 3456 15");
+    }
+
+    #[test]
+    fn digit_letter_switching() {
+        // A letter after digits ends the number, a digit after letters starts a new one.
+        assert_eq!(dots("12а"), "3456 1 12 56 1");
+        // Only the letters written like digits need the separator.
+        assert_eq!(dots("5д"), "3456 15 56 145");
+        assert_eq!(dots("5к"), "3456 15 13");
+        assert_eq!(dots("а12"), "1 3456 1 12");
+        // The Latin prefix is written again after a digit or a Cyrillic letter.
+        assert_eq!(dots("ab"), "6 1 12");
+        assert_eq!(dots("a1b"), "6 1 3456 1 6 12");
+        assert_eq!(dots("aяb"), "6 1 1246 6 12");
+    }
+
+    #[test]
+    fn punctuation_marks() {
+        assert_eq!(dots("«да»"), "236 145 1 356");
+        assert_eq!(dots("а — б"), "1 0 36 36 0 12");
+    }
+}
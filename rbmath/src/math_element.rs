@@ -1,13 +1,56 @@
+use std::ops::Range;
+
 use roxmltree::Node;
 use smallvec::SmallVec;
 
 use crate::{
-    constants::{NUMBER_SIGN, PLACEHOLDER},
-    letters::Classifier,
-    symbol_tables,
+    constants::{DECIMAL_MARK, GROUP_SEPARATOR, NUMBER_SIGN, PLACEHOLDER},
+    letters::{Classifier, Script},
+    literary,
+    symbol_tables::{self, LargeOperator, SymbolClass},
     traits::Renderer,
+    Chemistry, DecimalMark, RenderOptions, Span,
 };
-use brl::{braille, BraillePattern::*, BrailleString};
+use brl::{braille, BraillePattern, BraillePattern::*, BrailleString};
+
+fn digit(c: char, lower: bool) -> BraillePattern {
+    match c {
+        '0' if lower => Braille356,
+        '0' => Braille245,
+        '1' if lower => Braille2,
+        '1' => Braille1,
+        '2' if lower => Braille23,
+        '2' => Braille12,
+        '3' if lower => Braille25,
+        '3' => Braille14,
+        '4' if lower => Braille256,
+        '4' => Braille145,
+        '5' if lower => Braille26,
+        '5' => Braille15,
+        '6' if lower => Braille235,
+        '6' => Braille124,
+        '7' if lower => Braille2356,
+        '7' => Braille1245,
+        '8' if lower => Braille236,
+        '8' => Braille125,
+        '9' if lower => Braille35,
+        '9' => Braille24,
+        _ => PLACEHOLDER,
+    }
+}
+
+fn is_decimal_mark(c: char, options: RenderOptions) -> bool {
+    match options.decimal_mark {
+        DecimalMark::Comma => c == ',' || c == '.',
+        DecimalMark::Point => c == '.',
+    }
+}
+
+// Spaces between digit groups, e.g. thin spaces in 1 000 000.
+fn is_group_separator(c: char, options: RenderOptions) -> bool {
+    matches!(c, ' ' | '\u{a0}' | '\u{2009}' | '\u{202f}' | '\'')
+        || (c == ',' && options.decimal_mark == DecimalMark::Point)
+}
 
 pub(crate) type ChildrenArray<'a> = [Box<MathElement<'a>>; 8];
 
@@ -17,6 +60,7 @@ pub(crate) enum ElementType {
     Number,
     Ident,
     Operator,
+    Text,
     Row,
     Sqrt,
     Radical,
@@ -39,18 +83,88 @@ pub (crate) enum SubscriptType {
     UnderOver,
 }
 
+// The base and the scripts of msub, msup, msubsup, munder, mover and munderover.
+pub(crate) struct Scripts<'e, 'a> {
+    pub base: &'e MathElement<'a>,
+    // Limits of large operators are placed the same way in inline and display formulas.
+    pub placement: SubscriptType,
+    pub lower: Option<&'e MathElement<'a>>,
+    pub upper: Option<&'e MathElement<'a>>,
+}
+
+impl Scripts<'_, '_> {
+    // Arrows of reactions are stretched over empty scripts, isotopes are aligned with them.
+    pub fn are_invisible(&self) -> bool {
+        self.lower
+            .iter()
+            .chain(&self.upper)
+            .all(|s| s.is_invisible())
+    }
+}
+
+// Notations made of several elements of a row.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) enum Construct {
+    // The braces and the separator of a set in set-builder notation, {x | x > 0}.
+    SetOpen,
+    SetSeparator,
+    SetClose,
+    // The brackets and the separator of an interval, [a; b). The flags tell whether each end is included.
+    IntervalOpen(bool, bool),
+    IntervalSeparator,
+    IntervalClose,
+    // The cross between two sets, A × B.
+    CartesianProduct,
+    // The argument of a function made of several terms but not put in brackets, which
+    // MathML gives as a row after function application.
+    Argument,
+    // The two arrows of a reversible reaction, which mhchem stacks from ⇀ and ↽.
+    Equilibrium,
+    ReverseArrow,
+}
+
+// Sets are usually named by a capital letter such as A or ℝ, maybe with a script as in ℝ².
+fn is_set_name(e: &MathElement) -> bool {
+    match e.element {
+        ElementType::Ident => {
+            let mut chars = e.text.chars();
+            matches!((chars.next(), chars.next()), (Some(c), None) if c.is_uppercase())
+        }
+        ElementType::Subscript | ElementType::Superscript | ElementType::Row
+            if !e.children.is_empty()
+                && (e.element != ElementType::Row || e.children.len() == 1) =>
+        {
+            is_set_name(&e.children[0])
+        }
+        _ => false,
+    }
+}
+
 pub(crate) struct MathElement<'a> {
-    element: ElementType,
-    children: SmallVec<ChildrenArray<'a>>,
-    text: &'a str,
+    pub element: ElementType,
+    pub children: SmallVec<ChildrenArray<'a>>,
+    pub text: &'a str,
+    pub source: Range<usize>,
 }
 
 impl<'a> MathElement<'a> {
+    pub fn new(
+        element: ElementType,
+        children: SmallVec<ChildrenArray<'a>>,
+        text: &'a str,
+        source: Range<usize>,
+    ) -> MathElement<'a> {
+        MathElement {
+            element,
+            children,
+            text,
+            source,
+        }
+    }
+
     pub fn from_xml(node: Node<'a, '_>) -> Option<MathElement<'a>> {
         if !node.is_element() {
-            panic!(
-                "MathElement::from_xml was called on a non-element node. This should never happen!"
-            );
+            return None;
         }
         let elem_type = match node.tag_name().name() {
             "math" => Some(ElementType::Root),
@@ -58,6 +172,11 @@ impl<'a> MathElement<'a> {
             "mn" => Some(ElementType::Number),
             "mi" => Some(ElementType::Ident),
             "mo" => Some(ElementType::Operator),
+            "mtext" => Some(ElementType::Text),
+            // Spaces keep their place among the arguments, e.g. under the arrow of \xrightarrow.
+            "mspace" => Some(ElementType::Row),
+            // mhchem shifts its indices and arrows with mpadded.
+            "mpadded" => Some(ElementType::Row),
             "mroot" => Some(ElementType::Radical),
             "msqrt" => Some(ElementType::Sqrt),
             "mfrac" => Some(ElementType::Fraction),
@@ -81,18 +200,36 @@ impl<'a> MathElement<'a> {
                     None
                 })
                 .collect();
+            // The arguments of msqrt form an inferred row.
+            let children = if elem_type == ElementType::Sqrt && children.len() > 1 {
+                let row = MathElement::new(ElementType::Row, children, "", node.range());
+                std::iter::once(Box::new(row)).collect()
+            } else {
+                children
+            };
             Some(MathElement {
                 element: elem_type,
                 children,
                 text: node.text().unwrap_or(""),
+                source: node.range(),
             })
+        } else if node.tag_name().name() == "menclose" {
+            Some(MathElement::from_enclosure(node))
+        } else if node.tag_name().name() == "mphantom" {
+            // Phantoms only take up space.
+            Some(MathElement::new(
+                ElementType::Row,
+                SmallVec::new(),
+                "",
+                node.range(),
+            ))
         } else {
             log::warn!("Unknown MathML tag {}", node.tag_name().name());
             None
         }
     }
 
-    fn is_integer(&self) -> bool {
+    pub fn is_integer(&self) -> bool {
         match self.element {
             ElementType::Number => self.text.chars().all(|c| c.is_ascii_digit()),
             ElementType::Row if self.children.len() == 1 => self.children[0].is_integer(),
@@ -109,7 +246,7 @@ impl<'a> MathElement<'a> {
         }
     }
 
-    fn is_operators(&self) -> bool {
+    pub fn is_operators(&self) -> bool {
         match self.element {
             ElementType::Operator => true,
             ElementType::Row => self.children.iter().all(|c| c.is_operators()),
@@ -117,120 +254,695 @@ impl<'a> MathElement<'a> {
         }
     }
 
-    fn is_single_ident(&self) -> bool {
+    pub fn is_single_ident(&self) -> bool {
         match self.element {
             ElementType::Ident => true,
             ElementType::Row if self.children.len() == 1 => self.children[0].is_single_ident(),
             _ => false,
         }
     }
-    pub fn render(&self, r: &mut impl crate::traits::Renderer) {
+
+    // An integer or a single letter, which makes a fraction simple, as in a/2.
+    pub fn is_simple(&self) -> bool {
+        self.is_integer() || self.is_single_ident()
+    }
+
+    // An integer followed by a fraction of integers, such as 2 1/3.
+    pub fn is_mixed_number(&self, fraction: &MathElement) -> bool {
+        self.element == ElementType::Number
+            && self.is_integer()
+            && fraction.element == ElementType::Fraction
+            && fraction.children.iter().all(|c| c.is_integer())
+    }
+
+    pub fn large_operator(&self) -> Option<LargeOperator> {
+        match self.element {
+            ElementType::Operator => {
+                let mut chars = self.text.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => symbol_tables::large_operator(c),
+                    _ => None,
+                }
+            }
+            ElementType::Ident if symbol_tables::LIMIT_FUNCTIONS.contains(&self.text) => {
+                Some(LargeOperator::Limit)
+            }
+            ElementType::Row if self.children.len() == 1 => self.children[0].large_operator(),
+            _ => None,
+        }
+    }
+
+    // Whether the element has exactly the arguments its layout needs, e.g. both parts of a fraction.
+    pub fn is_complete(&self) -> bool {
         use ElementType::*;
+        let arity = match self.element {
+            Sqrt => 1,
+            Radical | Fraction | Subscript | Superscript | Underscript | Overscript => 2,
+            SubSuperscript | UnderOverscript => 3,
+            Root | Row | Number | Ident | Operator | Text => return true,
+        };
+        self.children.len() == arity
+    }
+
+    pub fn scripts(&self) -> Option<Scripts<'_, 'a>> {
         use SubscriptType::*;
+        let placement = match self.element {
+            ElementType::Subscript => Sub,
+            ElementType::Superscript => Sup,
+            ElementType::SubSuperscript => SubSup,
+            ElementType::Underscript => Under,
+            ElementType::Overscript => Over,
+            ElementType::UnderOverscript => UnderOver,
+            _ => return None,
+        };
+        if !self.is_complete() {
+            return None;
+        }
+        let placement = match (self.children[0].large_operator(), placement) {
+            (Some(LargeOperator::Integral), Sub | Under) => Sub,
+            (Some(LargeOperator::Integral), Sup | Over) => Sup,
+            (Some(LargeOperator::Integral), SubSup | UnderOver) => SubSup,
+            (Some(LargeOperator::Sum | LargeOperator::Limit), Sub | Under) => Under,
+            (Some(LargeOperator::Sum | LargeOperator::Limit), Sup | Over) => Over,
+            (Some(LargeOperator::Sum | LargeOperator::Limit), SubSup | UnderOver) => UnderOver,
+            (None, placement) => placement,
+        };
+        let (lower, upper) = match placement {
+            Sub | Under => (Some(&*self.children[1]), None),
+            Sup | Over => (None, Some(&*self.children[1])),
+            SubSup | UnderOver => (Some(&*self.children[1]), Some(&*self.children[2])),
+        };
+        Some(Scripts {
+            base: &self.children[0],
+            placement,
+            lower,
+            upper,
+        })
+    }
+
+    // The line or the arc over the points of a segment or an arc, which is written and read before them.
+    pub fn figure_sign(&self) -> Option<&'a str> {
+        let scripts = self.scripts()?;
+        if scripts.placement != SubscriptType::Over || !scripts.base.is_point_name() {
+            return None;
+        }
+        scripts
+            .upper?
+            .operator_text()
+            .filter(|t| matches!(*t, "‾" | "⌒"))
+    }
+
+    // A point of a geometric figure, a capital Latin letter maybe with an index, as in A₁.
+    pub fn is_point(&self) -> bool {
+        match self.element {
+            ElementType::Ident => {
+                let mut chars = self.text.chars();
+                matches!((chars.next(), chars.next()), (Some(c), None) if c.is_ascii_uppercase())
+            }
+            ElementType::Subscript if self.children.len() == 2 => self.children[0].is_point(),
+            _ => false,
+        }
+    }
+
+    // A sign such as ∠, which is followed by the names of the points of a figure.
+    pub fn is_figure_sign(&self) -> bool {
+        let mut chars = self.text.chars();
+        matches!(self.element, ElementType::Ident | ElementType::Operator)
+            && matches!((chars.next(), chars.next()), (Some(c), None) if symbol_tables::is_figure_sign(c))
+    }
+
+    // Two or more points naming a segment or an arc, as in \overline{AB}.
+    pub fn is_point_name(&self) -> bool {
+        match self.element {
+            ElementType::Ident => {
+                self.text.chars().count() > 1 && self.text.chars().all(|c| c.is_ascii_uppercase())
+            }
+            ElementType::Row if self.children.len() == 1 => self.children[0].is_point_name(),
+            ElementType::Row => {
+                self.children.len() > 1 && self.children.iter().all(|c| c.is_point())
+            }
+            _ => false,
+        }
+    }
+
+    // Function application and spaces, which produce no braille.
+    pub fn is_invisible(&self) -> bool {
+        match self.element {
+            ElementType::Operator => matches!(self.text.trim(), "" | "\u{2061}"),
+            ElementType::Row => self.children.iter().all(|c| c.is_invisible()),
+            _ => false,
+        }
+    }
+
+    // A function name, maybe with a power or a base as in sin² or log₂, or a row ending with one.
+    pub fn is_function(&self) -> bool {
+        match self.element {
+            ElementType::Ident => symbol_tables::operator_name(self.text).is_some(),
+            ElementType::Subscript | ElementType::Superscript | ElementType::SubSuperscript => {
+                !self.children.is_empty() && self.children[0].is_function()
+            }
+            ElementType::Row => self
+                .children
+                .iter()
+                .rev()
+                .find(|c| !c.is_invisible())
+                .is_some_and(|c| c.is_function()),
+            _ => false,
+        }
+    }
+
+    // A script without a base, which mhchem writes for counts of atoms and charges.
+    fn is_detached_script(&self) -> bool {
+        matches!(
+            self.element,
+            ElementType::Subscript | ElementType::Superscript | ElementType::SubSuperscript
+        ) && self
+            .children
+            .first()
+            .is_some_and(|b| b.element == ElementType::Row && b.is_invisible())
+    }
+
+    fn letter(&self) -> Option<char> {
+        match self.element {
+            ElementType::Ident => {
+                let mut chars = self.text.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Some(c),
+                    _ => None,
+                }
+            }
+            ElementType::Row => {
+                let mut items = self.children.iter().filter(|c| !c.is_invisible());
+                match (items.next(), items.next()) {
+                    (Some(c), None) => c.letter(),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    // An element symbol such as Na, which mhchem splits into letters, a group in brackets or
+    // a count after one of them.
+    fn ends_with_element(&self) -> bool {
+        match self.element {
+            ElementType::Ident => {
+                let mut chars = self.text.chars();
+                matches!(
+                    (chars.next(), chars.next(), chars.next()),
+                    (Some(c), None | Some('a'..='z'), None) if c.is_ascii_uppercase()
+                )
+            }
+            ElementType::Operator => self.text.trim() == ")",
+            ElementType::Row => {
+                let items: Vec<&MathElement> = self
+                    .children
+                    .iter()
+                    .map(|c| c.as_ref())
+                    .filter(|c| !c.is_invisible())
+                    .collect();
+                match items.as_slice() {
+                    [.., a, b] if b.letter().is_some_and(|c| c.is_ascii_lowercase()) => {
+                        a.letter().is_some_and(|c| c.is_ascii_uppercase())
+                    }
+                    [.., last] => last.ends_with_element(),
+                    [] => false,
+                }
+            }
+            _ => self.is_detached_script(),
+        }
+    }
+
+    // mhchem writes counts of atoms and charges as scripts without a base right after the
+    // element symbol. Prescripts such as {}_n C_k have nothing before them.
+    pub fn is_chemistry(&self) -> bool {
+        self.children.iter().enumerate().any(|(i, c)| {
+            (i > 0 && c.is_detached_script() && self.children[i - 1].ends_with_element())
+                || c.is_chemistry()
+        })
+    }
+
+    // The charge of an ion such as 2- or +, a number and a sign in either order.
+    pub fn charge(&self) -> Option<String> {
+        fn collect(e: &MathElement, res: &mut String) -> bool {
+            match e.element {
+                ElementType::Number | ElementType::Operator => {
+                    res.push_str(e.text.trim());
+                    true
+                }
+                ElementType::Row => e.children.iter().all(|c| collect(c, res)),
+                _ => false,
+            }
+        }
+        let mut res = String::new();
+        if !collect(self, &mut res) {
+            return None;
+        }
+        let digits = res
+            .trim_start_matches(['+', '-'])
+            .trim_end_matches(['+', '-']);
+        let signs = res.len() - digits.len();
+        (signs == 1 && digits.chars().all(|c| c.is_ascii_digit())).then_some(res)
+    }
+
+    // An arrow stretched over empty scripts, as mhchem writes the arrows of reactions.
+    fn arrow(&self) -> Option<&'a str> {
+        use ElementType::*;
+        match self.element {
+            Operator => Some(self.text.trim()),
+            Row => {
+                let mut items = self.children.iter().filter(|c| !c.is_invisible());
+                match (items.next(), items.next()) {
+                    (Some(c), None) => c.arrow(),
+                    _ => None,
+                }
+            }
+            Underscript | Overscript | UnderOverscript
+                if self.scripts().is_some_and(|s| s.are_invisible()) =>
+            {
+                self.children[0].arrow()
+            }
+            _ => None,
+        }
+    }
+
+    fn operator_text(&self) -> Option<&'a str> {
+        (self.element == ElementType::Operator).then_some(self.text.trim())
+    }
+
+    // Terms joined by signs of operations or relations, whose end a reader can't see after the
+    // name of a function unless they are in brackets, as in sin (x + 1).
+    fn is_compound(&self) -> bool {
+        if self.element != ElementType::Row {
+            return false;
+        }
+        let items: Vec<&MathElement> = self
+            .children
+            .iter()
+            .map(|c| c.as_ref())
+            .filter(|c| !c.is_invisible())
+            .collect();
+        if let [item] = items.as_slice() {
+            return item.is_compound();
+        }
+        let mut depth = 0;
+        // Where the bracket opening the row is closed, if it does.
+        let mut closed = None;
+        let mut signs = false;
+        for (i, c) in items.iter().enumerate() {
+            match c.operator_text() {
+                Some("(" | "[" | "{") => depth += 1,
+                Some(")" | "]" | "}") => depth -= 1,
+                Some(text) if depth == 0 && i > 0 => {
+                    signs |= text
+                        .chars()
+                        .any(|ch| symbol_tables::symbol_class(ch) != SymbolClass::Other)
+                }
+                _ => {}
+            }
+            if depth == 0 && closed.is_none() {
+                closed = Some(i);
+            }
+        }
+        let fenced = closed == Some(items.len() - 1)
+            && matches!(items[0].operator_text(), Some("(" | "[" | "{"));
+        signs && !fenced
+    }
+
+    // Finds set-builder notation, intervals and Cartesian products among the children of a row.
+    pub fn constructs(&self) -> Vec<Option<Construct>> {
+        let children = &self.children;
+        let mut res = vec![None; children.len()];
+        let (mut open, mut separator) = (None, None);
+        for (i, c) in children.iter().enumerate() {
+            match c.operator_text() {
+                Some("{") => (open, separator) = (Some(i), None),
+                Some("|" | "∣" | ":") if open.is_some() && separator.is_none() => {
+                    separator = Some(i)
+                }
+                Some("}") => {
+                    if let (Some(open), Some(separator)) = (open, separator) {
+                        res[open] = Some(Construct::SetOpen);
+                        res[separator] = Some(Construct::SetSeparator);
+                        res[i] = Some(Construct::SetClose);
+                    }
+                    (open, separator) = (None, None);
+                }
+                Some("×")
+                    if i > 0
+                        && i + 1 < children.len()
+                        && is_set_name(&children[i - 1])
+                        && is_set_name(&children[i + 1]) =>
+                {
+                    res[i] = Some(Construct::CartesianProduct)
+                }
+                _ => {}
+            }
+        }
+        // The next bracket or separator after the given position.
+        let delimiter = |from: usize| {
+            (from..children.len()).find(|&i| {
+                matches!(
+                    children[i].operator_text(),
+                    Some("(" | ")" | "[" | "]" | "{" | "}" | "|" | ";" | ",")
+                )
+            })
+        };
+        for (i, c) in children.iter().enumerate() {
+            let Some(open @ ("(" | "[")) = c.operator_text() else {
+                continue;
+            };
+            // The arguments of a function, as in НОД(12; 18).
+            if children[..i]
+                .iter()
+                .rev()
+                .find(|c| !c.is_invisible())
+                .is_some_and(|p| p.is_function())
+            {
+                continue;
+            }
+            let Some(j) = delimiter(i + 1).filter(|&j| j > i + 1) else {
+                continue;
+            };
+            let Some(k) = delimiter(j + 1).filter(|&k| k > j + 1) else {
+                continue;
+            };
+            let (Some(separator), Some(close @ (")" | "]"))) =
+                (children[j].operator_text(), children[k].operator_text())
+            else {
+                continue;
+            };
+            // (a, b) is more often a pair than an interval.
+            if separator == ";" || (separator == "," && (open == "[" || close == "]")) {
+                res[i] = Some(Construct::IntervalOpen(open == "[", close == "]"));
+                res[j] = Some(Construct::IntervalSeparator);
+                res[k] = Some(Construct::IntervalClose);
+            }
+        }
+        for (i, c) in children.iter().enumerate() {
+            if c.arrow() == Some("⇀") {
+                if let Some(j) = (i + 1..children.len()).find(|&j| !children[j].is_invisible()) {
+                    if children[j].arrow() == Some("↽") {
+                        res[i] = Some(Construct::Equilibrium);
+                        res[j] = Some(Construct::ReverseArrow);
+                    }
+                }
+            }
+            if !c.is_function() {
+                continue;
+            }
+            if let Some(j) = (i + 1..children.len()).find(|&j| !children[j].is_invisible()) {
+                if res[j].is_none() && children[j].is_compound() {
+                    res[j] = Some(Construct::Argument);
+                }
+            }
+        }
+        res
+    }
+
+    // Only the line over the contents, which TeX writes as \overline, has a meaning of its own.
+    fn from_enclosure(node: Node<'a, '_>) -> MathElement<'a> {
+        let source = node.range();
+        let children = node
+            .children()
+            .filter_map(MathElement::from_xml)
+            .map(Box::new)
+            .collect();
+        let contents = MathElement::new(ElementType::Row, children, "", source.clone());
+        let notation = node.attribute("notation").unwrap_or("longdiv");
+        if notation.split_whitespace().any(|n| n == "top") {
+            let bar = MathElement::new(
+                ElementType::Operator,
+                SmallVec::new(),
+                "‾",
+                source.end..source.end,
+            );
+            MathElement::new(
+                ElementType::Overscript,
+                SmallVec::from_iter([Box::new(contents), Box::new(bar)]),
+                "",
+                source,
+            )
+        } else {
+            contents
+        }
+    }
+
+    fn spanned<R: Renderer>(&self, r: &mut R, f: impl FnOnce(&Self, &mut R)) {
+        let start = r.position();
+        f(self, r);
+        r.push_span(Span {
+            cells: start..r.position(),
+            source: self.source.clone(),
+        });
+    }
+
+    pub fn render(&self, r: &mut impl crate::traits::Renderer) {
+        self.spanned(r, Self::render_element);
+    }
+
+    fn render_lower_number(&self, r: &mut impl Renderer) {
+        self.spanned(r, |e, r| {
+            if e.element == ElementType::Row {
+                for c in &e.children {
+                    if c.is_integer() {
+                        c.render_lower_number(r);
+                    } else {
+                        c.render(r);
+                    }
+                }
+            } else {
+                e.render_number(r, true);
+            }
+        });
+    }
+
+    fn render_element(&self, r: &mut impl Renderer) {
+        use ElementType::*;
+        if !self.is_complete() {
+            log::warn!("Wrong number of arguments of a MathML element, rendering a placeholder.");
+            self.render_children(r);
+            r.write_string(BrailleString::from(PLACEHOLDER), self.element);
+            return;
+        }
         match self.element {
             Root | Row => self.render_children(r),
             Number => self.render_number(r, false),
             Ident => self.render_ident(r),
             Operator => self.render_operator(r),
+            Text => self.render_text(r),
             Sqrt => self.render_radical(r, true),
             Radical => self.render_radical(r, false),
             Fraction => self.render_fraction(r),
-            Subscript => self.render_subscript(r, Sub),
-            Superscript => self.render_subscript(r, Sup),
-            SubSuperscript => self.render_subscript(r, SubSup),
-            Underscript => self.render_subscript(r, Under),
-            Overscript => self.render_subscript(r, Over),
-            UnderOverscript => self.render_subscript(r, UnderOver),
+            Subscript | Superscript | SubSuperscript | Underscript | Overscript
+            | UnderOverscript => self.render_subscript(r),
         }
     }
 
     fn render_children(&self, r: &mut impl Renderer) {
-        for c in &self.children {
-            c.render(r);
+        for (c, construct) in self.children.iter().zip(self.constructs()) {
+            match construct {
+                // Unlike the bars of an absolute value, the separator stands apart from its neighbours.
+                Some(Construct::SetSeparator) => c.spanned(r, |e, r| {
+                    let separator = match e.text.trim() {
+                        ":" => braille!(0, 6, 25, 0),
+                        _ => braille!(0, 456, 0),
+                    };
+                    r.write_string(separator, e.element)
+                }),
+                Some(Construct::CartesianProduct) => {
+                    c.spanned(r, |e, r| r.write_string(braille!(0, 236), e.element))
+                }
+                Some(Construct::Equilibrium) => c.spanned(r, |_, r| {
+                    let mut res = braille!(0);
+                    res += &symbol_tables::braille_symbol('⇌');
+                    r.write_string(res, ElementType::Operator);
+                }),
+                Some(Construct::ReverseArrow) => {}
+                Some(Construct::Argument) => c.spanned(r, |e, r| {
+                    r.write_string(symbol_tables::braille_symbol('('), ElementType::Operator);
+                    e.render(r);
+                    r.write_string(symbol_tables::braille_symbol(')'), ElementType::Operator);
+                }),
+                _ => c.render(r),
+            }
         }
     }
 
     fn render_number(&self, r: &mut impl Renderer, lower: bool) {
+        let options = r.options();
+        let chars: Vec<char> = self.text.trim().chars().collect();
         let mut res = BrailleString::new();
-        if !lower {
-            res += NUMBER_SIGN;
-        }
-        for c in self.text.chars() {
-            res += match c {
-                '0' if lower => Braille356,
-                '0' => Braille245,
-                '1' if lower => Braille2,
-                '1' => Braille1,
-                '2' if lower => Braille23,
-                '2' => Braille12,
-                '3' if lower => Braille25,
-                '3' => Braille14,
-                '4' if lower => Braille256,
-                '4' => Braille145,
-                '5' if lower => Braille26,
-                '5' => Braille15,
-                '6' if lower => Braille235,
-                '6' => Braille124,
-                '7' if lower => Braille2356,
-                '7' => Braille1245,
-                '8' if lower => Braille236,
-                '8' => Braille125,
-                '9' if lower => Braille35,
-                '9' => Braille24,
-                ',' | '.' => Braille2,
+        // Digits that continue a number don't repeat the number sign. MathJax splits numbers
+        // before scripts, e.g. 10^\circ becomes 1 followed by 0 with a superscript.
+        let mut in_number = !lower && r.get_open_number();
+        // Digits of the exponent in scientific notation are written lower.
+        let mut exponent = false;
+        for (i, &c) in chars.iter().enumerate() {
+            let next = chars.get(i + 1).copied();
+            let before_digit = next.is_some_and(|n| n.is_ascii_digit());
+            match c {
+                '0'..='9' => {
+                    if !in_number && !lower && !exponent {
+                        res += NUMBER_SIGN;
+                    }
+                    in_number = true;
+                    res += digit(c, lower || exponent);
+                }
+                c if is_group_separator(c, options) => {
+                    if in_number && before_digit {
+                        res += GROUP_SEPARATOR;
+                    }
+                }
+                c if is_decimal_mark(c, options) => {
+                    if !in_number && !lower && !exponent {
+                        res += NUMBER_SIGN;
+                    }
+                    in_number = true;
+                    res += DECIMAL_MARK;
+                }
+                // The period of a repeating decimal, such as 0,(3).
+                '(' if in_number => res += Braille126,
+                ')' if in_number => res += Braille345,
+                'e' | 'E'
+                    if in_number
+                        && !exponent
+                        && next.is_some_and(|n| n.is_ascii_digit() || n == '-' || n == '+') =>
+                {
+                    res += &symbol_tables::braille_symbol('⋅');
+                    if !lower {
+                        res += NUMBER_SIGN;
+                    }
+                    res += digit('1', lower);
+                    res += digit('0', lower);
+                    res += Braille34;
+                    in_number = false;
+                    exponent = true;
+                }
+                '+' | '-' | '±' | '∓' | '⋅' | '·' | '×' => {
+                    res += &symbol_tables::braille_symbol(c);
+                    in_number = false;
+                }
                 other => {
                     log::warn!(
                         "Trying to render non-numeric character {} as part of number.",
                         other
                     );
-                    PLACEHOLDER
+                    res += PLACEHOLDER;
                 }
             }
         }
         r.write_string(res, self.element);
+        r.set_open_number(!lower && !exponent && chars.last().is_some_and(|c| c.is_ascii_digit()));
     }
 
     fn render_ident(&self, r: &mut impl Renderer) {
         let mut res = BrailleString::new();
-        if let Some(f) = symbol_tables::special_function(self.text) {
+        // A single Latin letter after a number is more likely a variable than a unit.
+        let unit = symbol_tables::is_unit(self.text)
+            && (self.text.chars().count() > 1
+                || self.text.chars().all(|c| literary::cyrillic_letter(c).is_some()));
+        // Only the first letter after a digit can be mistaken for one, lowered digits of an index can't.
+        let mut after_digit = r.get_open_number();
+        let unit = unit && r.get_last_element_type() == Some(ElementType::Number);
+        if unit {
+            res += BrailleEmpty;
+            r.set_last_classifier(None);
+            after_digit = false;
+        }
+        // Cyrillic letters have no prefix that would end the name of a function before them.
+        let after_function = r.get_open_function();
+        let chemistry = r.options().chemistry == Chemistry::On;
+        if let Some(f) = symbol_tables::operator_name(self.text).filter(|_| !unit && !chemistry) {
             res += &f;
             r.set_last_classifier(None);
-        } else {
-            for c in self.text.chars() {
-                if let Some(l) = Classifier::classify(c) {
-                    let dots = l.get_rendering();
-                    if let Some(last_class) = r.get_last_classifier() {
-                        if last_class != l.1 {
-                            res += &l.1.get_prefix();
-                        } else if let Some(last_type) = r.get_last_element_type() {
-                            if last_type == ElementType::Number && !dots.has_lower_dots() {
-                                res += &l.1.get_prefix();
-                            }
-                        }
-                    } else {
-                        res += &l.1.get_prefix();
-                    }
-                    res += dots;
-                    r.set_last_classifier(Some(l.1));
+            r.write_string(res, self.element);
+            r.set_open_function(true);
+            return;
+        }
+        for c in self.text.chars() {
+            if let Some(l) = Classifier::classify(c) {
+                let dots = l.get_rendering();
+                let last_class = r.get_last_classifier();
+                // Every symbol of an element starts with the capital sign, its small letter continues it.
+                let prefix = if chemistry && l.1.script == Script::Latin {
+                    l.1.capital || last_class.is_none_or(|c| c.script != Script::Latin)
                 } else {
-                    res += &symbol_tables::braille_symbol(c);
+                    last_class.is_none_or(|c| c != l.1 || (after_digit && !dots.has_lower_dots()))
+                };
+                if prefix {
+                    res += &l.1.get_prefix();
+                }
+                res += dots;
+                r.set_last_classifier(Some(l.1));
+            } else if let Some(l) = literary::cyrillic_letter(c) {
+                if after_function && res.is_empty() {
+                    res += BrailleEmpty;
                 }
+                res += l;
+                r.set_last_classifier(None);
+            } else {
+                res += &symbol_tables::braille_symbol(c);
             }
+            after_digit = false;
+        }
+        r.write_string(res, self.element);
+    }
+
+    fn render_text(&self, r: &mut impl Renderer) {
+        let text = self.text.trim();
+        let mut res = BrailleString::new();
+        // Words after a number are always separated, otherwise their first letters would read as digits.
+        if r.position() > 0
+            && (self.text.starts_with(char::is_whitespace)
+                || r.get_last_element_type() == Some(ElementType::Number)
+                || r.get_open_function())
+        {
+            res += BrailleEmpty;
+        }
+        res += &literary::render_text(text);
+        if !text.is_empty() && self.text.ends_with(char::is_whitespace) {
+            res += BrailleEmpty;
         }
+        r.set_last_classifier(None);
         r.write_string(res, self.element);
     }
 
     fn render_operator(&self, r: &mut impl Renderer) {
-        for c in self.text.chars() {
-            if c == '+' || c == '-' || c == '±' || c == '∓' {
-                if let Some(ElementType::Number)
-                | Some(ElementType::Ident)
-                | Some(ElementType::Fraction)
-                | Some(ElementType::Sqrt)
-                | Some(ElementType::Radical) = r.get_last_element_type()
-                {
-                    r.write_string(braille!(0), self.element);
-                }
+        // Function application only ties the argument to the name, which stays open before it.
+        if self.text.trim() == "\u{2061}" {
+            return;
+        }
+        // The name of a function isn't an operand, the sign in sin -x belongs to the argument.
+        let operand = !r.get_open_function()
+            && matches!(
+                r.get_last_element_type(),
+                Some(
+                    ElementType::Number
+                        | ElementType::Ident
+                        | ElementType::Fraction
+                        | ElementType::Sqrt
+                        | ElementType::Radical
+                )
+            );
+        // In chemistry the signs only join substances, which may end with a charge.
+        let operand = operand || r.options().chemistry == Chemistry::On;
+        // Word operators such as mod in a \bmod b.
+        if let Some(f) = symbol_tables::special_function(self.text.trim()) {
+            let mut res = braille!(0);
+            res += &f;
+            r.write_string(res, self.element);
+            r.set_last_classifier(None);
+            return;
+        }
+        for (i, c) in self.text.chars().enumerate() {
+            let blank = match symbol_tables::symbol_class(c) {
+                SymbolClass::Relation => true,
+                SymbolClass::Operation => i == 0 && operand,
+                SymbolClass::Other => false,
+            };
+            if blank {
+                r.write_string(braille!(0), self.element);
             }
             r.write_string(symbol_tables::braille_symbol(c), self.element);
         }
@@ -240,7 +952,7 @@ impl<'a> MathElement<'a> {
         r.write_string(braille!(146), self.element);
         if !is_sqrt {
             if self.children[1].is_integer() {
-                self.children[1].render_number(r, true);
+                self.children[1].render_lower_number(r);
             } else {
                 self.children[1].render(r);
             }
@@ -253,16 +965,16 @@ impl<'a> MathElement<'a> {
     fn render_fraction(&self, r: &mut impl Renderer) {
         let num = &self.children[0];
         let denom = &self.children[1];
+        // After a number the fraction is part of a mixed number and starts with its own number sign.
+        r.set_open_number(false);
         if num.is_integer() && denom.is_integer() {
-            num.render_number(r, false);
-            denom.render_number(r, true);
-        } else if (num.is_integer() || num.is_single_ident())
-            && (denom.is_integer() || denom.is_single_ident())
-        {
+            num.render(r);
+            denom.render_lower_number(r);
+        } else if num.is_simple() && denom.is_simple() {
             num.render(r);
             r.write_string(braille!(1256), self.element);
             if denom.is_integer() {
-                denom.render_number(r, true);
+                denom.render_lower_number(r);
             } else {
                 denom.render(r);
             }
@@ -282,7 +994,40 @@ impl<'a> MathElement<'a> {
         parent: ElementType,
     ) {
         use SubscriptType::*;
-        if self.is_operators() {
+        // Counts of atoms are written with lower digits right after the element and charges of
+        // ions after the superscript sign without an end mark, as in SO₄²⁻.
+        if r.options().chemistry == Chemistry::On {
+            if self.is_invisible() {
+                return;
+            }
+            // mhchem aligns the numbers of isotopes with phantoms.
+            let mut items = self.children.iter().filter(|c| !c.is_invisible());
+            if let (ElementType::Row, Some(c), None) = (self.element, items.next(), items.next()) {
+                return c.render_in_script(r, script_type, parent);
+            }
+            if matches!(script_type, Sub | SubSup) && self.is_integer() {
+                self.render_lower_number(r);
+                return;
+            }
+            if let Some(charge) = self.charge().filter(|_| script_type == Sup) {
+                r.write_string(braille!(34), parent);
+                self.spanned(r, |e, r| {
+                    let mut res = BrailleString::new();
+                    for c in charge.chars() {
+                        res += &match c {
+                            '0'..='9' => BrailleString::from(digit(c, true)),
+                            _ => symbol_tables::braille_symbol(c),
+                        };
+                    }
+                    r.write_string(res, e.element);
+                });
+                return;
+            }
+        }
+        if script_type == Sup && self.element == ElementType::Operator && self.text == "∘" {
+            // The ring in 10^\circ is a degree sign.
+            r.write_string(symbol_tables::braille_symbol('°'), parent);
+        } else if self.is_operators() {
             self.render_embelishment(r, script_type, parent);
         }else {
         r.write_string(
@@ -297,7 +1042,7 @@ impl<'a> MathElement<'a> {
             parent,
         );
         if self.is_integer() {
-            self.render_number(r, true);
+            self.render_lower_number(r);
         } else {
             self.render(r);
             r.write_string(braille!(156), parent);
@@ -308,7 +1053,10 @@ impl<'a> MathElement<'a> {
     fn render_embelishment(&self, r: &mut impl Renderer, script_type: SubscriptType, parent: ElementType) {
         use SubscriptType::*;
         if let ElementType::Operator = self.element {
-            if !symbol_tables::has_affinity(self.text.chars().next().unwrap_or('0'), script_type) {
+            // Signs such as + in 0^+ look like lower digits, so a number before them needs the prefix.
+            if !symbol_tables::has_affinity(self.text.chars().next().unwrap_or('0'), script_type)
+                || r.get_last_element_type() == Some(ElementType::Number)
+            {
                 r.write_string(match script_type {
                     Under | UnderOver => braille!(56),
                     Sub | SubSup => braille!(456),
@@ -316,7 +1064,12 @@ impl<'a> MathElement<'a> {
                     Sup => braille!(46)
                 }, parent);
             }
-            self.render(r);
+            // Unlike binary operators, embellishments are never spaced.
+            self.spanned(r, |e, r| {
+                for c in e.text.chars() {
+                    r.write_string(symbol_tables::braille_symbol(c), e.element);
+                }
+            });
         } else {
             for c in &self.children {
                 c.render_embelishment(r, script_type, parent);
@@ -324,14 +1077,146 @@ impl<'a> MathElement<'a> {
         }
     }
 
-    fn render_subscript(&self, r: &mut impl Renderer, subscript_type: SubscriptType) {
+    fn render_subscript(&self, r: &mut impl Renderer) {
         use SubscriptType::*;
-        self.children[0].render(r);
-        self.children[1].render_in_script(r, subscript_type, self.element);
-        if let SubSup = subscript_type {
-            self.children[2].render_in_script(r, Sup, self.element);
-        } else if let UnderOver = subscript_type {
-            self.children[2].render_in_script(r, Over, self.element);
+        let scripts = self.scripts().unwrap();
+        if let Some(sign) = self.figure_sign() {
+            let sign = match sign {
+                "‾" => braille!(456, 25),
+                _ => symbol_tables::braille_symbol('⌒'),
+            };
+            r.write_string(sign, ElementType::Operator);
+            scripts.base.render(r);
+            return;
+        }
+        scripts.base.render(r);
+        match (scripts.placement, scripts.lower, scripts.upper) {
+            (SubSup, Some(lower), Some(upper)) => {
+                lower.render_in_script(r, SubSup, self.element);
+                upper.render_in_script(r, Sup, self.element);
+            }
+            (UnderOver, Some(lower), Some(upper)) => {
+                lower.render_in_script(r, UnderOver, self.element);
+                upper.render_in_script(r, Over, self.element);
+            }
+            (placement, lower, upper) => {
+                if let Some(script) = lower.or(upper) {
+                    script.render_in_script(r, placement, self.element);
+                }
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{render_with_options, Chemistry, DecimalMark, RenderOptions};
+
+    fn number(text: &str, decimal_mark: DecimalMark) -> String {
+        let ml = format!("<math><mn>{}</mn></math>", text);
+        render_with_options(
+            &ml,
+            RenderOptions {
+                decimal_mark,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .to_dots()
+    }
+
+    #[test]
+    fn decimal_marks() {
+        assert_eq!(number("3,14", DecimalMark::Comma), "3456 14 2 1 145");
+        assert_eq!(number("3.14", DecimalMark::Comma), "3456 14 2 1 145");
+        assert_eq!(number("3.14", DecimalMark::Point), "3456 14 2 1 145");
+        assert_eq!(
+            number("1,000.5", DecimalMark::Point),
+            "3456 1 3 245 245 245 2 15"
+        );
+    }
+
+    #[test]
+    fn group_separators() {
+        assert_eq!(
+            number("10 000", DecimalMark::Comma),
+            "3456 1 245 3 245 245 245"
+        );
+        assert_eq!(
+            number("10\u{a0}000", DecimalMark::Comma),
+            "3456 1 245 3 245 245 245"
+        );
+    }
+
+    #[test]
+    fn custom_function_names() {
+        let render = |ident: &str| {
+            let ml = format!("<math><mi>{}</mi><mi>x</mi></math>", ident);
+            crate::render(&ml).unwrap().to_dots()
+        };
+        assert_eq!(render("div"), "1246 145 24 1236 6 1346");
+        crate::register_function("rot", brl::braille!(1246, 1235, 2345));
+        assert_eq!(render("rot"), "1246 1235 2345 6 1346");
+        crate::clear_custom_functions();
+        assert_eq!(render("rot"), "1246 1235 135 2345 6 1346");
+    }
+
+    #[test]
+    fn argument_count() {
+        let render = |ml: &str| {
+            let ml = format!("<math>{}</math>", ml);
+            crate::render(&ml).unwrap().to_dots()
+        };
+        // The arguments of msqrt form a row.
+        assert_eq!(
+            render("<msqrt><mi>x</mi><mo>+</mo><mn>1</mn></msqrt>"),
+            "146 156 6 1346 0 235 3456 1 1456"
+        );
+        // Missing and extra arguments are all rendered, followed by a placeholder.
+        assert_eq!(render("<msqrt></msqrt>"), "123456");
+        assert_eq!(
+            render("<msub><mi>x</mi><mn>1</mn><mn>2</mn></msub>"),
+            "6 1346 3456 1 12 123456"
+        );
+    }
+
+    #[test]
+    fn chemistry_option() {
+        let render = |ml: &str, chemistry| {
+            let options = RenderOptions {
+                chemistry,
+                ..Default::default()
+            };
+            render_with_options(ml, options).unwrap().to_dots()
+        };
+        let salt = "<math><mi>N</mi><mi>a</mi><mi>C</mi><mi>l</mi></math>";
+        assert_eq!(render(salt, Chemistry::Off), "46 1345 6 1 46 14 6 123");
+        assert_eq!(render(salt, Chemistry::On), "46 1345 1 46 14 123");
+        // Counts after element symbols, as mhchem writes H₂O.
+        let water = "<math><mrow><mi>H</mi></mrow><msub><mrow></mrow><mn>2</mn></msub>\
+                     <mrow><mi>O</mi></mrow></math>";
+        assert_eq!(render(water, Chemistry::Off), "46 125 16 23 135");
+        assert_eq!(render(water, Chemistry::Auto), "46 125 23 46 135");
+        // A prescript has no element before it.
+        let prescript =
+            "<math><msub><mrow></mrow><mi>n</mi></msub><msub><mi>C</mi><mi>k</mi></msub>\
+            <mo>+</mo><mi>sin</mi><mo>\u{2061}</mo><mi>x</mi></math>";
+        assert_eq!(
+            render(prescript, Chemistry::Auto),
+            render(prescript, Chemistry::Off)
+        );
+    }
+
+    #[test]
+    fn signs_and_exponents() {
+        assert_eq!(number("+7", DecimalMark::Comma), "235 3456 1245");
+        assert_eq!(
+            number("2E3", DecimalMark::Comma),
+            "3456 12 3 3456 1 245 34 25"
+        );
+        assert_eq!(
+            number("2e+3", DecimalMark::Comma),
+            "3456 12 3 3456 1 245 34 235 25"
+        );
+    }
+}
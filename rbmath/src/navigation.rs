@@ -0,0 +1,434 @@
+// Structural exploration of a formula, for reading large expressions on a braille display.
+use std::{collections::HashMap, ops::Range};
+
+use brl::BrailleString;
+use smallvec::SmallVec;
+
+use crate::{
+    math_element::{ElementType, MathElement},
+    normalize_source, render_element,
+    speech::Language,
+    RenderError, RenderOptions,
+};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum NodeKind {
+    Formula,
+    Row,
+    Number,
+    Identifier,
+    Operator,
+    Text,
+    SquareRoot,
+    Radical,
+    Fraction,
+    Subscript,
+    Superscript,
+    SubSuperscript,
+    Underscript,
+    Overscript,
+    UnderOverscript,
+}
+
+// The place a node takes in its parent.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Role {
+    Formula,
+    Item,
+    Numerator,
+    Denominator,
+    Radicand,
+    Index,
+    Base,
+    Subscript,
+    Superscript,
+    Underscript,
+    Overscript,
+}
+
+struct Node {
+    kind: NodeKind,
+    role: Role,
+    text: String,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    cells: Range<usize>,
+    source: Range<usize>,
+}
+
+impl From<ElementType> for NodeKind {
+    fn from(value: ElementType) -> Self {
+        match value {
+            ElementType::Root => NodeKind::Formula,
+            ElementType::Row => NodeKind::Row,
+            ElementType::Number => NodeKind::Number,
+            ElementType::Ident => NodeKind::Identifier,
+            ElementType::Operator => NodeKind::Operator,
+            ElementType::Text => NodeKind::Text,
+            ElementType::Sqrt => NodeKind::SquareRoot,
+            ElementType::Radical => NodeKind::Radical,
+            ElementType::Fraction => NodeKind::Fraction,
+            ElementType::Subscript => NodeKind::Subscript,
+            ElementType::Superscript => NodeKind::Superscript,
+            ElementType::SubSuperscript => NodeKind::SubSuperscript,
+            ElementType::Underscript => NodeKind::Underscript,
+            ElementType::Overscript => NodeKind::Overscript,
+            ElementType::UnderOverscript => NodeKind::UnderOverscript,
+        }
+    }
+}
+
+fn child_role(parent: ElementType, index: usize) -> Role {
+    use ElementType::*;
+    match (parent, index) {
+        (Fraction, 0) => Role::Numerator,
+        (Fraction, _) => Role::Denominator,
+        (Sqrt, _) | (Radical, 0) => Role::Radicand,
+        (Radical, _) => Role::Index,
+        (
+            Subscript | Superscript | SubSuperscript | Underscript | Overscript | UnderOverscript,
+            0,
+        ) => Role::Base,
+        (Subscript | SubSuperscript, 1) => Role::Subscript,
+        (Superscript | SubSuperscript, _) => Role::Superscript,
+        (Underscript | UnderOverscript, 1) => Role::Underscript,
+        (Overscript | UnderOverscript, _) => Role::Overscript,
+        _ => Role::Item,
+    }
+}
+
+fn kind_name(kind: NodeKind, language: Language) -> &'static str {
+    let (ru, en) = match kind {
+        NodeKind::Formula => ("формула", "formula"),
+        NodeKind::Row => ("выражение", "expression"),
+        NodeKind::Number => ("число", "number"),
+        NodeKind::Identifier => ("переменная", "identifier"),
+        NodeKind::Operator => ("знак", "operator"),
+        NodeKind::Text => ("текст", "text"),
+        NodeKind::SquareRoot => ("квадратный корень", "square root"),
+        NodeKind::Radical => ("корень", "root"),
+        NodeKind::Fraction => ("дробь", "fraction"),
+        NodeKind::Subscript => ("нижний индекс", "subscript"),
+        NodeKind::Superscript => ("степень", "superscript"),
+        NodeKind::SubSuperscript => ("индекс и степень", "subscript and superscript"),
+        NodeKind::Underscript => ("надпись снизу", "underscript"),
+        NodeKind::Overscript => ("надпись сверху", "overscript"),
+        NodeKind::UnderOverscript => ("надписи снизу и сверху", "underscript and overscript"),
+    };
+    match language {
+        Language::Russian => ru,
+        Language::English => en,
+    }
+}
+
+fn role_name(role: Role, language: Language) -> Option<&'static str> {
+    let (ru, en) = match role {
+        Role::Formula | Role::Item => return None,
+        Role::Numerator => ("числитель", "numerator"),
+        Role::Denominator => ("знаменатель", "denominator"),
+        Role::Radicand => ("подкоренное выражение", "radicand"),
+        Role::Index => ("показатель корня", "root index"),
+        Role::Base => ("основание", "base"),
+        Role::Subscript => ("индекс", "subscript"),
+        Role::Superscript => ("показатель степени", "exponent"),
+        Role::Underscript => ("снизу", "underscript"),
+        Role::Overscript => ("сверху", "overscript"),
+    };
+    Some(match language {
+        Language::Russian => ru,
+        Language::English => en,
+    })
+}
+
+// A rendered formula with a cursor that moves over its elements.
+pub struct Navigator {
+    braille: BrailleString,
+    nodes: Vec<Node>,
+    focus: usize,
+}
+
+impl Navigator {
+    pub fn new(ml: &str) -> Result<Navigator, RenderError> {
+        let (normalized, offsets) = normalize_source(ml);
+        let document = roxmltree::Document::parse(&normalized)?;
+        let root = document.root_element();
+        if root.tag_name().name() != "math" {
+            return Err(RenderError::NotMathML(root.tag_name().name().to_string()));
+        }
+        let Some(math) = MathElement::from_xml(root) else {
+            return Err(RenderError::NotMathML(String::new()));
+        };
+        let (braille, spans) = render_element(&math, RenderOptions::default());
+        let cells: HashMap<(usize, usize), Range<usize>> = spans
+            .into_iter()
+            .map(|s| ((s.source.start, s.source.end), s.cells))
+            .collect();
+        let mut res = Navigator {
+            braille,
+            nodes: Vec::new(),
+            focus: 0,
+        };
+        res.add_node(&math, Role::Formula, None, &cells, &offsets);
+        Ok(res)
+    }
+
+    fn add_node(
+        &mut self,
+        e: &MathElement,
+        role: Role,
+        parent: Option<usize>,
+        cells: &HashMap<(usize, usize), Range<usize>>,
+        offsets: &[usize],
+    ) {
+        // Rows with a single element only add a level without meaning, so they are skipped.
+        if e.element == ElementType::Row && e.children.len() == 1 {
+            return self.add_node(&e.children[0], role, parent, cells, offsets);
+        }
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            kind: e.element.into(),
+            role,
+            text: e.text.to_string(),
+            parent,
+            children: Vec::new(),
+            cells: cells
+                .get(&(e.source.start, e.source.end))
+                .cloned()
+                .unwrap_or(0..0),
+            source: offsets[e.source.start]..offsets[e.source.end],
+        });
+        if let Some(parent) = parent {
+            self.nodes[parent].children.push(index);
+        }
+        // The same goes for a row that makes up the whole formula.
+        let mut items = e;
+        while matches!(items.element, ElementType::Root | ElementType::Row)
+            && items.children.len() == 1
+            && items.children[0].element == ElementType::Row
+        {
+            items = &items.children[0];
+        }
+        for (i, c) in items.children.iter().enumerate() {
+            self.add_node(c, child_role(e.element, i), Some(index), cells, offsets);
+        }
+        // Elements that produce no braille, such as invisible operators, can't be focused.
+        if let Some(parent) = parent {
+            let node = &self.nodes[index];
+            if node.cells.is_empty() && node.children.is_empty() {
+                self.nodes[parent].children.pop();
+            }
+        }
+    }
+
+    fn node(&self) -> &Node {
+        &self.nodes[self.focus]
+    }
+
+    fn move_to(&mut self, target: Option<usize>) -> bool {
+        match target {
+            Some(target) => {
+                self.focus = target;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn sibling(&self, offset: isize) -> Option<usize> {
+        let siblings = &self.nodes[self.node().parent?].children;
+        let position = siblings.iter().position(|&c| c == self.focus)?;
+        siblings.get(position.checked_add_signed(offset)?).copied()
+    }
+
+    // The rendering of the whole formula.
+    pub fn braille(&self) -> &BrailleString {
+        &self.braille
+    }
+
+    // The rendering of the focused element.
+    pub fn focus_braille(&self) -> BrailleString {
+        BrailleString::from(SmallVec::from_slice(&self.braille[self.focus_cells()]))
+    }
+
+    // Position of the focused element in the rendering of the whole formula.
+    pub fn focus_cells(&self) -> Range<usize> {
+        self.node().cells.clone()
+    }
+
+    // Byte range of the focused element in the MathML source.
+    pub fn focus_source(&self) -> Range<usize> {
+        self.node().source.clone()
+    }
+
+    pub fn kind(&self) -> NodeKind {
+        self.node().kind
+    }
+
+    pub fn role(&self) -> Role {
+        self.node().role
+    }
+
+    // Text of numbers, identifiers, operators and text elements; empty for other elements.
+    pub fn text(&self) -> &str {
+        &self.node().text
+    }
+
+    pub fn depth(&self) -> usize {
+        let mut res = 0;
+        let mut node = self.focus;
+        while let Some(parent) = self.nodes[node].parent {
+            res += 1;
+            node = parent;
+        }
+        res
+    }
+
+    // A short description for announcing the focus, such as "знаменатель: дробь".
+    pub fn describe(&self, language: Language) -> String {
+        let node = self.node();
+        let mut res = String::new();
+        if let Some(role) = role_name(node.role, language) {
+            res += role;
+            res += ": ";
+        }
+        res += kind_name(node.kind, language);
+        if !node.text.is_empty() {
+            res += " ";
+            res += &node.text;
+        }
+        res
+    }
+
+    pub fn to_root(&mut self) {
+        self.focus = 0;
+    }
+
+    pub fn to_parent(&mut self) -> bool {
+        self.move_to(self.node().parent)
+    }
+
+    pub fn to_first_child(&mut self) -> bool {
+        self.move_to(self.node().children.first().copied())
+    }
+
+    pub fn to_last_child(&mut self) -> bool {
+        self.move_to(self.node().children.last().copied())
+    }
+
+    pub fn to_next_sibling(&mut self) -> bool {
+        self.move_to(self.sibling(1))
+    }
+
+    pub fn to_previous_sibling(&mut self) -> bool {
+        self.move_to(self.sibling(-1))
+    }
+
+    // Focuses the innermost element rendered at the given cell, e.g. after pressing a routing key.
+    pub fn to_cell(&mut self, cell: usize) -> bool {
+        let mut target = None;
+        let mut node = 0;
+        while self.nodes[node].cells.contains(&cell) {
+            target = Some(node);
+            match self.nodes[node]
+                .children
+                .iter()
+                .find(|&&c| self.nodes[c].cells.contains(&cell))
+            {
+                Some(&child) => node = child,
+                None => break,
+            }
+        }
+        self.move_to(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // (a + 1)/√x + y²
+    const FORMULA: &str = "<math><mfrac><mrow><mi>a</mi><mo>+</mo><mn>1</mn></mrow>\
+        <msqrt><mi>x</mi></msqrt></mfrac><mo>+</mo><msup><mi>y</mi><mn>2</mn></msup></math>";
+
+    fn describe(n: &Navigator) -> String {
+        n.describe(Language::Russian)
+    }
+
+    #[test]
+    fn fraction_and_root() {
+        let mut n = Navigator::new(FORMULA).unwrap();
+        assert_eq!(describe(&n), "формула");
+        assert!(n.to_first_child());
+        assert_eq!(describe(&n), "дробь");
+        assert!(n.to_first_child());
+        assert_eq!(describe(&n), "числитель: выражение");
+        assert!(n.to_last_child());
+        assert_eq!((n.kind(), n.text(), n.depth()), (NodeKind::Number, "1", 3));
+        assert!(n.to_parent());
+        assert!(n.to_next_sibling());
+        assert_eq!(describe(&n), "знаменатель: квадратный корень");
+        assert!(n.to_first_child());
+        assert_eq!(describe(&n), "подкоренное выражение: переменная x");
+        assert!(n.to_parent() && n.to_parent());
+        assert_eq!(n.kind(), NodeKind::Fraction);
+    }
+
+    #[test]
+    fn scripts() {
+        let mut n = Navigator::new(FORMULA).unwrap();
+        assert!(n.to_last_child());
+        assert_eq!(describe(&n), "степень");
+        assert!(n.to_first_child());
+        assert_eq!(describe(&n), "основание: переменная y");
+        assert!(n.to_next_sibling());
+        assert_eq!(describe(&n), "показатель степени: число 2");
+        assert_eq!(n.describe(Language::English), "exponent: number 2");
+        assert!(n.to_previous_sibling());
+        assert_eq!(n.role(), Role::Base);
+    }
+
+    #[test]
+    fn radical_index() {
+        let mut n = Navigator::new("<math><mroot><mi>x</mi><mn>3</mn></mroot></math>").unwrap();
+        assert!(n.to_first_child());
+        assert_eq!(n.kind(), NodeKind::Radical);
+        assert!(n.to_last_child());
+        assert_eq!(describe(&n), "показатель корня: число 3");
+    }
+
+    #[test]
+    fn boundaries() {
+        let mut n = Navigator::new(FORMULA).unwrap();
+        assert!(!n.to_parent());
+        assert!(!n.to_next_sibling());
+        assert!(!n.to_previous_sibling());
+        assert!(n.to_first_child());
+        // Moves past the first or the last item fail and keep the focus.
+        assert!(!n.to_previous_sibling());
+        assert_eq!(n.kind(), NodeKind::Fraction);
+        assert!(n.to_next_sibling() && n.to_next_sibling());
+        assert!(!n.to_next_sibling());
+        assert_eq!(n.kind(), NodeKind::Superscript);
+        assert!(n.to_first_child());
+        assert!(!n.to_first_child());
+        assert_eq!(n.text(), "y");
+        n.to_root();
+        assert_eq!((n.kind(), n.depth()), (NodeKind::Formula, 0));
+    }
+
+    #[test]
+    fn focus_braille() {
+        let mut n = Navigator::new(FORMULA).unwrap();
+        assert_eq!(n.focus_braille().to_dots(), n.braille().to_dots());
+        assert_eq!(n.focus_cells(), 0..n.braille().len());
+        assert!(n.to_last_child() && n.to_last_child());
+        let exponent = n.focus_cells();
+        assert!(!exponent.is_empty() && exponent.end <= n.braille().len());
+        assert_eq!(n.focus_braille().len(), exponent.len());
+        assert_eq!(&FORMULA[n.focus_source()], "<mn>2</mn>");
+        n.to_root();
+        assert!(n.to_cell(exponent.start));
+        assert_eq!(n.text(), "2");
+        assert!(!n.to_cell(n.braille().len()));
+    }
+}
@@ -0,0 +1,473 @@
+// Office Math Markup Language, the equation format of Microsoft Word.
+use std::ops::Range;
+
+use brl::BrailleString;
+use roxmltree::{Document, Node};
+
+use crate::{
+    math_element::{ElementType, MathElement},
+    normalize_source, render_element, symbol_tables, RenderError, RenderOptions,
+};
+
+const OMML_NAMESPACE: &str = "http://schemas.openxmlformats.org/officeDocument/2006/math";
+
+fn is_omml(node: &Node, name: &str) -> bool {
+    node.is_element()
+        && node.tag_name().namespace() == Some(OMML_NAMESPACE)
+        && node.tag_name().name() == name
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|c| is_omml(c, name))
+}
+
+// Reads the m:val attribute of a property such as m:dPr/m:begChr.
+fn property<'a>(node: Node<'a, '_>, properties: &str, name: &str) -> Option<&'a str> {
+    child(node, properties)
+        .and_then(|p| child(p, name))
+        .map(|p| p.attribute((OMML_NAMESPACE, "val")).unwrap_or("on"))
+}
+
+fn is_on(value: Option<&str>) -> bool {
+    matches!(value, Some("1" | "on" | "true"))
+}
+
+fn leaf<'a>(element: ElementType, text: &'a str, source: Range<usize>) -> MathElement<'a> {
+    MathElement::new(element, Default::default(), text, source)
+}
+
+fn element<'a>(
+    element: ElementType,
+    children: Vec<MathElement<'a>>,
+    source: Range<usize>,
+) -> MathElement<'a> {
+    MathElement::new(
+        element,
+        children.into_iter().map(Box::new).collect(),
+        "",
+        source,
+    )
+}
+
+fn row<'a>(node: Option<Node<'a, '_>>) -> MathElement<'a> {
+    match node {
+        Some(node) => element(ElementType::Row, convert_children(node), node.range()),
+        None => element(ElementType::Row, Vec::new(), 0..0),
+    }
+}
+
+fn accent(chr: &str) -> &str {
+    match chr {
+        "\u{302}" => "^",
+        "\u{303}" => "~",
+        "\u{307}" => "˙",
+        "\u{304}" | "\u{305}" | "\u{af}" => "‾",
+        "\u{20d7}" => "→",
+        other => other,
+    }
+}
+
+// Word stores text as runs, which have to be split into numbers, identifiers and operators.
+fn tokenize<'a>(text: &'a str, source: Range<usize>, res: &mut Vec<MathElement<'a>>) {
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let (element, len) = if c.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == ',' || c == '.'))
+                .unwrap_or(rest.len());
+            let len = rest[..len].trim_end_matches([',', '.']).len();
+            (Some(ElementType::Number), len)
+        } else if c.is_alphabetic() {
+            let word_len = rest
+                .find(|c: char| !c.is_alphabetic())
+                .unwrap_or(rest.len());
+            if symbol_tables::special_function(&rest[..word_len]).is_some() {
+                (Some(ElementType::Ident), word_len)
+            } else {
+                (Some(ElementType::Ident), c.len_utf8())
+            }
+        } else if c.is_whitespace() {
+            (None, c.len_utf8())
+        } else {
+            (Some(ElementType::Operator), c.len_utf8())
+        };
+        if let Some(element) = element {
+            res.push(leaf(element, &rest[..len], source.clone()));
+        }
+        rest = &rest[len..];
+    }
+}
+
+fn convert_children<'a>(node: Node<'a, '_>) -> Vec<MathElement<'a>> {
+    node.children().flat_map(convert).collect()
+}
+
+fn convert<'a>(node: Node<'a, '_>) -> Vec<MathElement<'a>> {
+    use ElementType::*;
+    if !node.is_element() || node.tag_name().namespace() != Some(OMML_NAMESPACE) {
+        return Vec::new();
+    }
+    let source = node.range();
+    let name = node.tag_name().name();
+    let e = || row(child(node, "e"));
+    match name {
+        "r" => {
+            let mut res = Vec::new();
+            for t in node.children().filter(|c| is_omml(c, "t")) {
+                tokenize(t.text().unwrap_or(""), t.range(), &mut res);
+            }
+            res
+        }
+        "f" => vec![element(
+            Fraction,
+            vec![row(child(node, "num")), row(child(node, "den"))],
+            source,
+        )],
+        "rad" => {
+            let degree = child(node, "deg").filter(|d| d.children().any(|c| c.is_element()));
+            if degree.is_none() || is_on(property(node, "radPr", "degHide")) {
+                vec![element(Sqrt, vec![e()], source)]
+            } else {
+                vec![element(Radical, vec![e(), row(degree)], source)]
+            }
+        }
+        "sSup" => vec![element(
+            Superscript,
+            vec![e(), row(child(node, "sup"))],
+            source,
+        )],
+        "sSub" => vec![element(
+            Subscript,
+            vec![e(), row(child(node, "sub"))],
+            source,
+        )],
+        "sSubSup" => vec![element(
+            SubSuperscript,
+            vec![e(), row(child(node, "sub")), row(child(node, "sup"))],
+            source,
+        )],
+        "limLow" => vec![element(
+            Underscript,
+            vec![e(), row(child(node, "lim"))],
+            source,
+        )],
+        "limUpp" => vec![element(
+            Overscript,
+            vec![e(), row(child(node, "lim"))],
+            source,
+        )],
+        "nary" => {
+            let chr = property(node, "naryPr", "chr").unwrap_or("∫");
+            let operator = leaf(Operator, chr, source.clone());
+            let sub = child(node, "sub").filter(|_| !is_on(property(node, "naryPr", "subHide")));
+            let sup = child(node, "sup").filter(|_| !is_on(property(node, "naryPr", "supHide")));
+            let under_over = match property(node, "naryPr", "limLoc") {
+                Some(loc) => loc == "undOvr",
+                None => chr != "∫",
+            };
+            let operator = match (sub, sup, under_over) {
+                (Some(_), Some(_), false) => element(
+                    SubSuperscript,
+                    vec![operator, row(sub), row(sup)],
+                    source.clone(),
+                ),
+                (Some(_), Some(_), true) => element(
+                    UnderOverscript,
+                    vec![operator, row(sub), row(sup)],
+                    source.clone(),
+                ),
+                (Some(_), None, false) => {
+                    element(Subscript, vec![operator, row(sub)], source.clone())
+                }
+                (Some(_), None, true) => {
+                    element(Underscript, vec![operator, row(sub)], source.clone())
+                }
+                (None, Some(_), false) => {
+                    element(Superscript, vec![operator, row(sup)], source.clone())
+                }
+                (None, Some(_), true) => {
+                    element(Overscript, vec![operator, row(sup)], source.clone())
+                }
+                (None, None, _) => operator,
+            };
+            vec![operator, e()]
+        }
+        "d" => {
+            let open = property(node, "dPr", "begChr").unwrap_or("(");
+            let close = property(node, "dPr", "endChr").unwrap_or(")");
+            let separator = property(node, "dPr", "sepChr").unwrap_or("|");
+            let mut children = Vec::new();
+            if !open.is_empty() {
+                children.push(leaf(Operator, open, source.clone()));
+            }
+            for (i, arg) in node.children().filter(|c| is_omml(c, "e")).enumerate() {
+                if i > 0 && !separator.is_empty() {
+                    children.push(leaf(Operator, separator, source.clone()));
+                }
+                children.push(row(Some(arg)));
+            }
+            if !close.is_empty() {
+                children.push(leaf(Operator, close, source.clone()));
+            }
+            vec![element(Row, children, source)]
+        }
+        "func" => {
+            let fname = child(node, "fName");
+            let texts: Vec<Node> = fname
+                .into_iter()
+                .flat_map(|f| f.descendants())
+                .filter(|d| is_omml(d, "t"))
+                .collect();
+            // Function names are written as a single run, which mustn't be split into letters.
+            let name = match texts.as_slice() {
+                [t] if t
+                    .text()
+                    .is_some_and(|t| t.trim().chars().all(char::is_alphabetic)) =>
+                {
+                    leaf(Ident, t.text().unwrap().trim(), t.range())
+                }
+                _ => row(fname),
+            };
+            let apply = leaf(Operator, "\u{2061}", source.end..source.end);
+            vec![name, apply, e()]
+        }
+        "acc" => {
+            let chr = accent(property(node, "accPr", "chr").unwrap_or("\u{302}"));
+            vec![element(
+                Overscript,
+                vec![e(), leaf(Operator, chr, source.clone())],
+                source,
+            )]
+        }
+        "bar" => {
+            let over = property(node, "barPr", "pos") == Some("top");
+            let bar = leaf(Operator, "‾", source.clone());
+            vec![element(
+                if over { Overscript } else { Underscript },
+                vec![e(), bar],
+                source,
+            )]
+        }
+        "groupChr" => {
+            let chr = property(node, "groupChrPr", "chr").unwrap_or("⏟");
+            let over = property(node, "groupChrPr", "pos") == Some("top");
+            let group = leaf(Operator, chr, source.clone());
+            vec![element(
+                if over { Overscript } else { Underscript },
+                vec![e(), group],
+                source,
+            )]
+        }
+        name if name.ends_with("Pr") => Vec::new(),
+        // Containers such as m:e, m:box or m:eqArr are flattened into their parent.
+        _ => convert_children(node),
+    }
+}
+
+fn render_math(node: Node) -> BrailleString {
+    let root = element(ElementType::Root, convert_children(node), node.range());
+    render_element(&root, RenderOptions::default()).0
+}
+
+fn equations<'a, 'input>(root: Node<'a, 'input>) -> impl Iterator<Item = Node<'a, 'input>> {
+    root.descendants()
+        .filter(|n| is_omml(n, "oMath") && !n.ancestors().skip(1).any(|a| is_omml(&a, "oMath")))
+}
+
+// Renders every equation of a document, such as word/document.xml from a DOCX file, in document order.
+pub fn render_document(document: &str) -> Result<Vec<BrailleString>, RenderError> {
+    let (normalized, _) = normalize_source(document);
+    let document = Document::parse(&normalized)?;
+    Ok(equations(document.root()).map(render_math).collect())
+}
+
+// Renders a single m:oMath or m:oMathPara element.
+pub fn render(omml: &str) -> Result<BrailleString, RenderError> {
+    let (normalized, _) = normalize_source(omml);
+    let document = Document::parse(&normalized)?;
+    let root = document.root_element();
+    if !is_omml(&root, "oMath") && !is_omml(&root, "oMathPara") {
+        return Err(RenderError::NotMathML(root.tag_name().name().to_string()));
+    }
+    let mut res = BrailleString::new();
+    for (i, equation) in equations(document.root()).enumerate() {
+        if i > 0 {
+            res += brl::BraillePattern::BrailleEmpty;
+        }
+        res += &render_math(equation);
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn omml(body: &str) -> String {
+        let equation = format!("<m:oMath xmlns:m=\"{}\">{}</m:oMath>", OMML_NAMESPACE, body);
+        render(&equation).unwrap().to_dots()
+    }
+
+    fn mathml(body: &str) -> String {
+        crate::render(&format!("<math>{}</math>", body))
+            .unwrap()
+            .to_dots()
+    }
+
+    fn run(text: &str) -> String {
+        format!("<m:r><m:t>{}</m:t></m:r>", text)
+    }
+
+    #[test]
+    fn fraction() {
+        assert_eq!(
+            omml(&format!(
+                "<m:f><m:num>{}</m:num><m:den>{}</m:den></m:f>",
+                run("1"),
+                run("2")
+            )),
+            mathml("<mfrac><mn>1</mn><mn>2</mn></mfrac>")
+        );
+        assert_eq!(
+            omml(&format!(
+                "<m:f><m:num>{}</m:num><m:den>{}</m:den></m:f>",
+                run("a+b"),
+                run("c")
+            )),
+            mathml("<mfrac><mrow><mi>a</mi><mo>+</mo><mi>b</mi></mrow><mi>c</mi></mfrac>")
+        );
+    }
+
+    #[test]
+    fn radical() {
+        assert_eq!(
+            omml(&format!("<m:rad><m:deg/><m:e>{}</m:e></m:rad>", run("x"))),
+            mathml("<msqrt><mi>x</mi></msqrt>")
+        );
+        assert_eq!(
+            omml(&format!(
+                "<m:rad><m:deg>{}</m:deg><m:e>{}</m:e></m:rad>",
+                run("3"),
+                run("x")
+            )),
+            mathml("<mroot><mi>x</mi><mn>3</mn></mroot>")
+        );
+        // A hidden degree is ignored.
+        assert_eq!(
+            omml(&format!(
+                "<m:rad><m:radPr><m:degHide m:val=\"1\"/></m:radPr><m:deg>{}</m:deg><m:e>{}</m:e></m:rad>",
+                run("3"),
+                run("x")
+            )),
+            mathml("<msqrt><mi>x</mi></msqrt>")
+        );
+    }
+
+    #[test]
+    fn scripts() {
+        assert_eq!(
+            omml(&format!(
+                "<m:sSup><m:e>{}</m:e><m:sup>{}</m:sup></m:sSup>",
+                run("x"),
+                run("2")
+            )),
+            mathml("<msup><mi>x</mi><mn>2</mn></msup>")
+        );
+        assert_eq!(
+            omml(&format!(
+                "<m:sSub><m:e>{}</m:e><m:sub>{}</m:sub></m:sSub>",
+                run("a"),
+                run("n")
+            )),
+            mathml("<msub><mi>a</mi><mi>n</mi></msub>")
+        );
+    }
+
+    #[test]
+    fn nary() {
+        let sum = format!(
+            "<m:nary><m:naryPr><m:chr m:val=\"∑\"/></m:naryPr><m:sub>{}</m:sub><m:sup>{}</m:sup><m:e>{}</m:e></m:nary>",
+            run("i=1"),
+            run("n"),
+            run("i")
+        );
+        assert_eq!(
+            omml(&sum),
+            mathml(
+                "<munderover><mo>∑</mo><mrow><mi>i</mi><mo>=</mo><mn>1</mn></mrow><mi>n</mi></munderover><mi>i</mi>"
+            )
+        );
+        // Integrals have their limits as scripts unless the location says otherwise.
+        let integral = format!(
+            "<m:nary><m:sub>{}</m:sub><m:sup>{}</m:sup><m:e>{}</m:e></m:nary>",
+            run("0"),
+            run("1"),
+            run("x")
+        );
+        assert_eq!(
+            omml(&integral),
+            mathml("<msubsup><mo>∫</mo><mn>0</mn><mn>1</mn></msubsup><mi>x</mi>")
+        );
+        let hidden = format!(
+            "<m:nary><m:naryPr><m:subHide m:val=\"1\"/><m:supHide m:val=\"1\"/></m:naryPr>\
+            <m:sub/><m:sup/><m:e>{}</m:e></m:nary>",
+            run("x")
+        );
+        assert_eq!(omml(&hidden), mathml("<mo>∫</mo><mi>x</mi>"));
+    }
+
+    #[test]
+    fn delimiters() {
+        assert_eq!(
+            omml(&format!("<m:d><m:e>{}</m:e></m:d>", run("x+1"))),
+            mathml("<mo>(</mo><mi>x</mi><mo>+</mo><mn>1</mn><mo>)</mo>")
+        );
+        assert_eq!(
+            omml(&format!(
+                "<m:d><m:dPr><m:begChr m:val=\"[\"/><m:endChr m:val=\"]\"/><m:sepChr m:val=\";\"/></m:dPr>\
+                <m:e>{}</m:e><m:e>{}</m:e></m:d>",
+                run("0"),
+                run("1")
+            )),
+            mathml("<mo>[</mo><mn>0</mn><mo>;</mo><mn>1</mn><mo>]</mo>")
+        );
+    }
+
+    #[test]
+    fn function() {
+        assert_eq!(
+            omml(&format!(
+                "<m:func><m:fName>{}</m:fName><m:e>{}</m:e></m:func>",
+                run("sin"),
+                run("x")
+            )),
+            mathml("<mi>sin</mi><mo>&#x2061;</mo><mi>x</mi>")
+        );
+        assert_eq!(
+            omml(&format!(
+                "<m:func><m:fName>{}</m:fName><m:e>{}</m:e></m:func>",
+                run("log"),
+                run("(x+1)")
+            )),
+            mathml("<mi>log</mi><mo>&#x2061;</mo><mrow><mo>(</mo><mi>x</mi><mo>+</mo><mn>1</mn><mo>)</mo></mrow>")
+        );
+    }
+
+    #[test]
+    fn document() {
+        let document = format!(
+            "<w:document xmlns:w=\"urn:w\" xmlns:m=\"{}\"><w:p><m:oMath>{}</m:oMath></w:p>\
+            <w:p><m:oMathPara><m:oMath>{}</m:oMath></m:oMathPara></w:p></w:document>",
+            OMML_NAMESPACE,
+            run("x"),
+            run("2")
+        );
+        let equations: Vec<String> = render_document(&document)
+            .unwrap()
+            .iter()
+            .map(|e| e.to_dots())
+            .collect();
+        assert_eq!(equations, [mathml("<mi>x</mi>"), mathml("<mn>2</mn>")]);
+        assert!(render("<math/>").is_err());
+    }
+}
@@ -1,32 +1,54 @@
-use crate::{letters::Classifier, math_element::ElementType, traits::Renderer};
+use crate::{
+    letters::Classifier, math_element::ElementType, traits::Renderer, RenderOptions, Span,
+};
 use brl::BrailleString;
 
 pub(crate) struct BasicRenderer {
+    options: RenderOptions,
     last_elem_type: Option<ElementType>,
     last_classifier: Option<Classifier>,
+    // Whether the output ends with the digits of a number that later digits can continue.
+    open_number: bool,
+    // Whether the output ends with a function name that a following letter could continue.
+    open_function: bool,
     res: BrailleString,
+    spans: Vec<Span>,
 }
 
 impl BasicRenderer {
-    pub fn new() -> BasicRenderer {
+    pub fn new(options: RenderOptions) -> BasicRenderer {
         BasicRenderer {
+            options,
             last_elem_type: None,
             last_classifier: None,
+            open_number: false,
+            open_function: false,
             res: BrailleString::new(),
+            spans: Vec::new(),
         }
     }
+
+    pub fn take_spans(&mut self) -> Vec<Span> {
+        std::mem::take(&mut self.spans)
+    }
 }
 
 impl Renderer for BasicRenderer {
     fn write_string(&mut self, s: BrailleString, source_type: ElementType) {
         self.res += &s;
         self.last_elem_type = Some(source_type);
+        self.open_number = false;
+        self.open_function = false;
     }
 
     fn finish(self) -> BrailleString {
         self.res
     }
 
+    fn options(&self) -> RenderOptions {
+        self.options
+    }
+
     fn get_last_classifier(&self) -> Option<crate::letters::Classifier> {
         self.last_classifier
     }
@@ -35,7 +57,31 @@ impl Renderer for BasicRenderer {
         self.last_classifier = cls;
     }
 
+    fn get_open_number(&self) -> bool {
+        self.open_number
+    }
+
+    fn set_open_number(&mut self, open: bool) {
+        self.open_number = open;
+    }
+
+    fn get_open_function(&self) -> bool {
+        self.open_function
+    }
+
+    fn set_open_function(&mut self, open: bool) {
+        self.open_function = open;
+    }
+
     fn get_last_element_type(&self) -> Option<ElementType> {
         self.last_elem_type
     }
+
+    fn position(&self) -> usize {
+        self.res.len()
+    }
+
+    fn push_span(&mut self, span: Span) {
+        self.spans.push(span);
+    }
 }
@@ -0,0 +1,1141 @@
+// Spoken rendering of formulas for screen reader users, in the spirit of ClearSpeak.
+use crate::{
+    letters::{Classifier, Font, Script},
+    math_element::{Construct, ElementType, MathElement, Scripts, SubscriptType},
+    normalize_source, symbol_tables, Chemistry, RenderError,
+};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum Language {
+    #[default]
+    Russian,
+    English,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+pub enum Verbosity {
+    // Only the words needed to tell the structure apart.
+    Brief,
+    #[default]
+    Medium,
+    // Every structure is announced together with its end.
+    Verbose,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct SpeechOptions {
+    pub language: Language,
+    pub verbosity: Verbosity,
+    pub chemistry: Chemistry,
+}
+
+const LATIN_NAMES_RU: [&str; 26] = [
+    "а",
+    "бэ",
+    "цэ",
+    "дэ",
+    "е",
+    "эф",
+    "же",
+    "аш",
+    "и",
+    "йот",
+    "ка",
+    "эль",
+    "эм",
+    "эн",
+    "о",
+    "пэ",
+    "ку",
+    "эр",
+    "эс",
+    "тэ",
+    "у",
+    "вэ",
+    "дубль-вэ",
+    "икс",
+    "игрек",
+    "зет",
+];
+
+const GREEK_NAMES_RU: [&str; 25] = [
+    "альфа",
+    "бета",
+    "гамма",
+    "дельта",
+    "эпсилон",
+    "дзета",
+    "эта",
+    "тета",
+    "йота",
+    "каппа",
+    "лямбда",
+    "мю",
+    "ню",
+    "кси",
+    "омикрон",
+    "пи",
+    "ро",
+    "сигма",
+    "сигма",
+    "тау",
+    "ипсилон",
+    "фи",
+    "хи",
+    "пси",
+    "омега",
+];
+
+const GREEK_NAMES_EN: [&str; 25] = [
+    "alpha", "beta", "gamma", "delta", "epsilon", "zeta", "eta", "theta", "iota", "kappa",
+    "lambda", "mu", "nu", "xi", "omicron", "pi", "rho", "sigma", "sigma", "tau", "upsilon", "phi",
+    "chi", "psi", "omega",
+];
+
+const UNITS_RU: [&str; 20] = [
+    "ноль",
+    "один",
+    "два",
+    "три",
+    "четыре",
+    "пять",
+    "шесть",
+    "семь",
+    "восемь",
+    "девять",
+    "десять",
+    "одиннадцать",
+    "двенадцать",
+    "тринадцать",
+    "четырнадцать",
+    "пятнадцать",
+    "шестнадцать",
+    "семнадцать",
+    "восемнадцать",
+    "девятнадцать",
+];
+
+const TENS_RU: [&str; 10] = [
+    "",
+    "",
+    "двадцать",
+    "тридцать",
+    "сорок",
+    "пятьдесят",
+    "шестьдесят",
+    "семьдесят",
+    "восемьдесят",
+    "девяносто",
+];
+
+const HUNDREDS_RU: [&str; 10] = [
+    "",
+    "сто",
+    "двести",
+    "триста",
+    "четыреста",
+    "пятьсот",
+    "шестьсот",
+    "семьсот",
+    "восемьсот",
+    "девятьсот",
+];
+
+// Singular, paucal and plural forms of thousands, millions and billions.
+const SCALES_RU: [[&str; 3]; 3] = [
+    ["тысяча", "тысячи", "тысяч"],
+    ["миллион", "миллиона", "миллионов"],
+    ["миллиард", "миллиарда", "миллиардов"],
+];
+
+const UNITS_EN: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const TENS_EN: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+const SCALES_EN: [&str; 3] = ["thousand", "million", "billion"];
+
+// Longer numbers, such as digit strings in codes, are read digit by digit.
+const MAX_SPOKEN_DIGITS: usize = 12;
+
+fn russian_form(n: u64) -> usize {
+    match (n % 10, n % 100) {
+        (_, 11..=14) => 2,
+        (1, _) => 0,
+        (2..=4, _) => 1,
+        _ => 2,
+    }
+}
+
+fn russian_triple(n: u64, feminine: bool, res: &mut Vec<String>) {
+    if n >= 100 {
+        res.push(HUNDREDS_RU[(n / 100) as usize].to_string());
+    }
+    let rest = n % 100;
+    if rest >= 20 {
+        res.push(TENS_RU[(rest / 10) as usize].to_string());
+    }
+    let units = if rest >= 20 { rest % 10 } else { rest };
+    match units {
+        0 => {}
+        1 if feminine => res.push("одна".to_string()),
+        2 if feminine => res.push("две".to_string()),
+        u => res.push(UNITS_RU[u as usize].to_string()),
+    }
+}
+
+fn russian_number(n: u64) -> Vec<String> {
+    if n == 0 {
+        return vec![UNITS_RU[0].to_string()];
+    }
+    let mut res = Vec::new();
+    for (i, scale) in SCALES_RU.iter().enumerate().rev() {
+        let group = n / 1000u64.pow(i as u32 + 1) % 1000;
+        if group > 0 {
+            russian_triple(group, i == 0, &mut res);
+            res.push(scale[russian_form(group)].to_string());
+        }
+    }
+    russian_triple(n % 1000, false, &mut res);
+    res
+}
+
+fn english_triple(n: u64, res: &mut Vec<String>) {
+    if n >= 100 {
+        res.push(UNITS_EN[(n / 100) as usize].to_string());
+        res.push("hundred".to_string());
+    }
+    match n % 100 {
+        0 => {}
+        rest @ 1..=19 => res.push(UNITS_EN[rest as usize].to_string()),
+        rest if rest % 10 == 0 => res.push(TENS_EN[(rest / 10) as usize].to_string()),
+        rest => res.push(format!(
+            "{}-{}",
+            TENS_EN[(rest / 10) as usize],
+            UNITS_EN[(rest % 10) as usize]
+        )),
+    }
+}
+
+fn english_number(n: u64) -> Vec<String> {
+    if n == 0 {
+        return vec![UNITS_EN[0].to_string()];
+    }
+    let mut res = Vec::new();
+    for (i, scale) in SCALES_EN.iter().enumerate().rev() {
+        let group = n / 1000u64.pow(i as u32 + 1) % 1000;
+        if group > 0 {
+            english_triple(group, &mut res);
+            res.push(scale.to_string());
+        }
+    }
+    english_triple(n % 1000, &mut res);
+    res
+}
+
+fn operator_name(c: char, language: Language) -> Option<&'static str> {
+    let (ru, en) = match c {
+        // Function application is heard from the name of the function.
+        '\u{2061}' => ("", ""),
+        '+' => ("плюс", "plus"),
+        '-' => ("минус", "minus"),
+        '±' => ("плюс-минус", "plus or minus"),
+        '∓' => ("минус-плюс", "minus or plus"),
+        '×' | '⋅' | '∗' => ("умножить на", "times"),
+        '÷' | '/' | '∶' => ("разделить на", "divided by"),
+        '∘' => ("композиция", "composed with"),
+        '=' => ("равно", "equals"),
+        '≠' => ("не равно", "is not equal to"),
+        '<' => ("меньше", "is less than"),
+        '>' => ("больше", "is greater than"),
+        '≤' => ("меньше или равно", "is less than or equal to"),
+        '≥' => ("больше или равно", "is greater than or equal to"),
+        '≈' => ("приблизительно равно", "is approximately equal to"),
+        '≃' => ("асимптотически равно", "is asymptotically equal to"),
+        '≮' => ("не меньше", "is not less than"),
+        '≯' => ("не больше", "is not greater than"),
+        '≰' => ("не меньше и не равно", "is neither less than nor equal to"),
+        '≱' => (
+            "не больше и не равно",
+            "is neither greater than nor equal to",
+        ),
+        '≪' => ("много меньше", "is much less than"),
+        '≫' => ("много больше", "is much greater than"),
+        '∝' => ("пропорционально", "is proportional to"),
+        '≡' => ("тождественно равно", "is identical to"),
+        '~' => ("тильда", "tilde"),
+        '(' => ("открывающая скобка", "open paren"),
+        ')' => ("закрывающая скобка", "close paren"),
+        '[' => ("открывающая квадратная скобка", "open bracket"),
+        ']' => ("закрывающая квадратная скобка", "close bracket"),
+        '{' => ("открывающая фигурная скобка", "open brace"),
+        '}' => ("закрывающая фигурная скобка", "close brace"),
+        '|' => ("вертикальная черта", "vertical bar"),
+        ',' => ("запятая", "comma"),
+        ';' => ("точка с запятой", "semicolon"),
+        ':' => ("двоеточие", "colon"),
+        '.' => ("точка", "dot"),
+        '!' => ("факториал", "factorial"),
+        '%' => ("процентов", "percent"),
+        '‰' => ("промилле", "per mille"),
+        '°' => ("градусов", "degrees"),
+        '℃' => ("градусов Цельсия", "degrees Celsius"),
+        '′' => ("штрих", "prime"),
+        '″' => ("два штриха", "double prime"),
+        '…' | '⋯' => ("и так далее", "dot dot dot"),
+        '∞' => ("бесконечность", "infinity"),
+        '∑' => ("сумма", "the sum"),
+        '∏' => ("произведение", "the product"),
+        '∫' => ("интеграл", "the integral"),
+        '∬' => ("двойной интеграл", "the double integral"),
+        '∭' => ("тройной интеграл", "the triple integral"),
+        '∮' => ("контурный интеграл", "the contour integral"),
+        '∯' => ("поверхностный интеграл", "the surface integral"),
+        '∰' => ("объёмный интеграл", "the volume integral"),
+        '∐' => ("копроизведение", "the coproduct"),
+        '⋃' => ("объединение", "the union"),
+        '⋂' => ("пересечение", "the intersection"),
+        '∂' => ("дэ", "partial"),
+        '∇' => ("набла", "nabla"),
+        '→' | '⟶' => ("стремится к", "approaches"),
+        '←' | '⟵' => ("стрелка влево", "left arrow"),
+        '↔' | '⟷' => ("стрелка влево-вправо", "left right arrow"),
+        '⇒' | '⟹' => ("следует", "implies"),
+        '⇐' | '⟸' => ("следует из", "is implied by"),
+        '⇔' | '⟺' => ("равносильно", "if and only if"),
+        '↦' | '⟼' => ("переходит в", "maps to"),
+        '↑' => ("стрелка вверх", "up arrow"),
+        '↓' => ("стрелка вниз", "down arrow"),
+        '↗' => ("стрелка вправо вверх", "north east arrow"),
+        '↘' => ("стрелка вправо вниз", "south east arrow"),
+        '↖' => ("стрелка влево вверх", "north west arrow"),
+        '↙' => ("стрелка влево вниз", "south west arrow"),
+        '⇌' | '⇋' => ("обратимо", "is in equilibrium with"),
+        '∈' => ("принадлежит", "is an element of"),
+        '∉' => ("не принадлежит", "is not an element of"),
+        '⊂' => ("является подмножеством", "is a subset of"),
+        '⊆' => (
+            "является подмножеством или равно",
+            "is a subset of or equal to",
+        ),
+        '⊄' => ("не является подмножеством", "is not a subset of"),
+        '⊃' => ("является надмножеством", "is a superset of"),
+        '⊇' => (
+            "является надмножеством или равно",
+            "is a superset of or equal to",
+        ),
+        '⊅' => ("не является надмножеством", "is not a superset of"),
+        '∋' => ("содержит", "contains"),
+        '∣' => ("делит", "divides"),
+        '∤' => ("не делит", "does not divide"),
+        '∪' => ("объединение", "union"),
+        '∩' => ("пересечение", "intersection"),
+        '∖' => ("разность", "minus"),
+        '∆' => ("симметрическая разность", "symmetric difference"),
+        '⊕' => ("прямая сумма", "direct sum"),
+        '∅' => ("пустое множество", "the empty set"),
+        '∧' => ("и", "and"),
+        '∨' => ("или", "or"),
+        '¬' => ("не", "not"),
+        '∀' => ("для любого", "for all"),
+        '∃' => ("существует", "there exists"),
+        '∄' => ("не существует", "there does not exist"),
+        'ℵ' => ("алеф", "aleph"),
+        '∠' => ("угол", "angle"),
+        '⊥' => ("перпендикулярно", "is perpendicular to"),
+        '∥' => ("параллельно", "is parallel to"),
+        '△' => ("треугольник", "triangle"),
+        '⌒' => ("дуга", "arc"),
+        '∼' => ("подобен", "is similar to"),
+        '≅' => ("равен", "is congruent to"),
+        _ => return None,
+    };
+    Some(match language {
+        Language::Russian => ru,
+        Language::English => en,
+    })
+}
+
+// Accents and other marks placed over or under a base. The second value tells whether the word precedes the base.
+fn accent_name(c: char, language: Language) -> Option<(&'static str, bool)> {
+    let (ru, en, prefix) = match c {
+        '→' | '⃗' => ("вектор", "vector", true),
+        '‾' | '¯' => ("с чертой", "bar", false),
+        '^' | 'ˆ' => ("с крышкой", "hat", false),
+        '~' | '˜' => ("с тильдой", "tilde", false),
+        '˙' => ("с точкой", "dot", false),
+        '¨' => ("с двумя точками", "double dot", false),
+        _ => return None,
+    };
+    Some((
+        match language {
+            Language::Russian => ru,
+            Language::English => en,
+        },
+        prefix,
+    ))
+}
+
+// Signs of reactions, which chemists read in their own words.
+fn reaction_sign_name(c: char, language: Language) -> Option<&'static str> {
+    let (ru, en) = match c {
+        '→' | '⟶' => ("образует", "yields"),
+        '↑' => ("газ", "gas"),
+        '↓' => ("осадок", "precipitate"),
+        _ => return None,
+    };
+    Some(match language {
+        Language::Russian => ru,
+        Language::English => en,
+    })
+}
+
+struct SpeechRenderer {
+    options: SpeechOptions,
+    words: Vec<String>,
+    // Points of a figure are known to be capital letters, so that isn't said.
+    points: bool,
+    // Formulas of substances are read as chemists do, "аш два о".
+    chemistry: bool,
+}
+
+impl SpeechRenderer {
+    fn ru_en(&self, ru: &'static str, en: &'static str) -> &'static str {
+        match self.options.language {
+            Language::Russian => ru,
+            Language::English => en,
+        }
+    }
+
+    fn say(&mut self, word: &str) {
+        if !word.is_empty() {
+            self.words.push(word.to_string());
+        }
+    }
+
+    fn pause(&mut self) {
+        if let Some(last) = self.words.last_mut() {
+            if !last.ends_with(',') {
+                last.push(',');
+            }
+        }
+    }
+
+    fn verbose(&self) -> bool {
+        self.options.verbosity == Verbosity::Verbose
+    }
+
+    fn brief(&self) -> bool {
+        self.options.verbosity == Verbosity::Brief
+    }
+
+    // Closes a structure whose end can't be heard otherwise.
+    fn end(&mut self, complex: bool, ru: &'static str, en: &'static str) {
+        if self.verbose() || (complex && !self.brief()) {
+            self.pause();
+            self.say(self.ru_en(ru, en));
+            self.pause();
+        }
+    }
+
+    fn element(&mut self, e: &MathElement) {
+        use ElementType::*;
+        if !e.is_complete() {
+            for c in &e.children {
+                self.element(c);
+            }
+            return;
+        }
+        match e.element {
+            Root | Row => {
+                let outer = self.points;
+                let mut points = outer;
+                for (i, (c, construct)) in e.children.iter().zip(e.constructs()).enumerate() {
+                    if i > 0 && e.children[i - 1].is_mixed_number(c) {
+                        self.say(self.ru_en("и", "and"));
+                    }
+                    points = points && (c.is_point() || c.is_point_name());
+                    self.points = points;
+                    match construct {
+                        Some(Construct::Argument) => {
+                            self.construct(Construct::Argument);
+                            self.element(c);
+                            self.end(true, "конец аргумента", "end argument");
+                        }
+                        Some(Construct::ReverseArrow) => {}
+                        Some(construct) => self.construct(construct),
+                        None => self.element(c),
+                    }
+                    points = points || c.is_figure_sign();
+                }
+                self.points = outer;
+            }
+            Number => self.number(e.text),
+            Ident => self.ident(e.text),
+            Text => self.say(e.text.trim()),
+            Operator if symbol_tables::special_function(e.text.trim()).is_some() => {
+                self.ident(e.text.trim())
+            }
+            Operator => {
+                for c in e.text.chars() {
+                    self.operator(c);
+                }
+            }
+            Sqrt => {
+                self.say(match self.options.verbosity {
+                    Verbosity::Verbose => self.ru_en("квадратный корень из", "the square root of"),
+                    _ => self.ru_en("корень из", "the square root of"),
+                });
+                self.element(&e.children[0]);
+                self.end(!e.children[0].is_simple(), "конец корня", "end root");
+            }
+            Radical => {
+                let index = &e.children[1];
+                if index.is_integer() && index.text == "3" && !self.verbose() {
+                    self.say(self.ru_en("кубический корень из", "the cube root of"));
+                } else {
+                    self.say(self.ru_en("корень степени", "the root of index"));
+                    self.element(index);
+                    self.say(self.ru_en("из", "of"));
+                }
+                self.element(&e.children[0]);
+                self.end(!e.children[0].is_simple(), "конец корня", "end root");
+            }
+            Fraction => self.fraction(e),
+            Subscript | Superscript | SubSuperscript | Underscript | Overscript
+            | UnderOverscript => self.scripts(e),
+        }
+    }
+
+    fn scripts(&mut self, e: &MathElement) {
+        use SubscriptType::*;
+        let scripts = e.scripts().unwrap();
+        if scripts.are_invisible() {
+            return self.element(scripts.base);
+        }
+        if scripts.base.large_operator().is_some() {
+            return self.large_operator(&scripts);
+        }
+        if let Some(sign) = e.figure_sign() {
+            self.say(match sign {
+                "‾" => self.ru_en("отрезок", "segment"),
+                _ => self.ru_en("дуга", "arc"),
+            });
+            self.points = true;
+            self.element(scripts.base);
+            self.points = false;
+            return;
+        }
+        match (scripts.placement, scripts.lower, scripts.upper) {
+            // The bar of F(x)|_a^b.
+            (SubSup, Some(lower), Some(upper))
+                if scripts.base.element == ElementType::Operator && scripts.base.text == "|" =>
+            {
+                self.say(self.ru_en("в пределах от", "evaluated from"));
+                self.element(lower);
+                self.say(self.ru_en("до", "to"));
+                self.element(upper);
+                self.pause();
+            }
+            (Sub | SubSup | Sup, lower, upper) => {
+                match lower {
+                    Some(lower) => self.subscript(scripts.base, lower),
+                    None => self.element(scripts.base),
+                }
+                if let Some(upper) = upper {
+                    self.superscript(upper);
+                }
+            }
+            (Under | UnderOver, Some(lower), upper) => {
+                self.script(scripts.base, lower, false);
+                if let Some(upper) = upper {
+                    self.pause();
+                    self.say(self.ru_en("сверху", "and above"));
+                    self.element(upper);
+                }
+            }
+            (_, lower, upper) => {
+                if let Some(script) = upper.or(lower) {
+                    self.script(scripts.base, script, true);
+                }
+            }
+        }
+    }
+
+    fn number(&mut self, text: &str) {
+        let (integer, fraction) = match text.find([',', '.']) {
+            Some(i) => (&text[..i], Some(&text[i + 1..])),
+            None => (text, None),
+        };
+        self.integer(integer);
+        if let Some(fraction) = fraction {
+            self.say(self.ru_en("запятая", "point"));
+            match self.options.language {
+                Language::Russian => {
+                    // Leading zeros of the fractional part are lost when it is read as a whole number.
+                    let digits = fraction.trim_start_matches('0');
+                    for _ in 0..fraction.len() - digits.len() {
+                        self.say(UNITS_RU[0]);
+                    }
+                    if !digits.is_empty() {
+                        self.integer(digits);
+                    }
+                }
+                Language::English => self.digits(fraction),
+            }
+        }
+    }
+
+    fn integer(&mut self, text: &str) {
+        if text.is_empty()
+            || text.len() > MAX_SPOKEN_DIGITS
+            || (text.len() > 1 && text.starts_with('0'))
+        {
+            return self.digits(text);
+        }
+        match text.parse::<u64>() {
+            Ok(n) => {
+                let words = match self.options.language {
+                    Language::Russian => russian_number(n),
+                    Language::English => english_number(n),
+                };
+                self.words.extend(words);
+            }
+            Err(_) => self.digits(text),
+        }
+    }
+
+    fn digits(&mut self, text: &str) {
+        for c in text.chars() {
+            match c.to_digit(10) {
+                Some(d) => self.say(self.ru_en(UNITS_RU[d as usize], UNITS_EN[d as usize])),
+                None => self.operator(c),
+            }
+        }
+    }
+
+    fn ident(&mut self, text: &str) {
+        if symbol_tables::special_function(text).is_some() {
+            match symbol_tables::spoken_function(text) {
+                Some((ru, en)) => self.say(self.ru_en(ru, en)),
+                None => self.say(text),
+            }
+        } else if text.chars().count() > 1 && text.chars().all(|c| c.is_ascii_alphabetic()) {
+            self.say(text);
+        } else {
+            for c in text.chars() {
+                self.letter(c);
+            }
+        }
+    }
+
+    fn letter(&mut self, c: char) {
+        let Some(letter) = Classifier::classify(c) else {
+            match operator_name(c, self.options.language) {
+                Some(name) => self.say(name),
+                None => self.say(&c.to_string()),
+            }
+            return;
+        };
+        let cls = letter.1;
+        let font = match cls.font {
+            Font::Normal => "",
+            Font::Bold => self.ru_en("жирная", "bold"),
+            Font::Fraktur => self.ru_en("готическая", "fraktur"),
+            Font::Calligraphic => self.ru_en("рукописная", "script"),
+            Font::BoldCalligraphic => self.ru_en("жирная рукописная", "bold script"),
+            Font::Doublestruck => self.ru_en("ажурная", "double-struck"),
+        };
+        self.say(font);
+        if cls.capital && !self.points && !self.chemistry {
+            self.say(self.ru_en("заглавная", "capital"));
+        }
+        let name = match (cls.script, self.options.language) {
+            (Script::Latin, Language::Russian) => LATIN_NAMES_RU[letter.0].to_string(),
+            (Script::Latin, Language::English) => char::from(b'a' + letter.0 as u8).to_string(),
+            (Script::Greek, Language::Russian) => GREEK_NAMES_RU[letter.0].to_string(),
+            (Script::Greek, Language::English) => GREEK_NAMES_EN[letter.0].to_string(),
+        };
+        self.say(&name);
+    }
+
+    fn construct(&mut self, construct: Construct) {
+        match construct {
+            Construct::SetOpen => self.say(self.ru_en("множество", "the set of")),
+            Construct::SetSeparator => self.say(self.ru_en("таких, что", "such that")),
+            Construct::IntervalOpen(true, true) => {
+                self.say(self.ru_en("отрезок от", "the closed interval from"))
+            }
+            Construct::IntervalOpen(false, false) => {
+                self.say(self.ru_en("интервал от", "the open interval from"))
+            }
+            Construct::IntervalOpen(..) => {
+                self.say(self.ru_en("полуинтервал от", "the half-open interval from"))
+            }
+            Construct::IntervalSeparator => self.say(self.ru_en("до", "to")),
+            Construct::SetClose | Construct::IntervalClose => self.pause(),
+            Construct::CartesianProduct => self.say(self.ru_en("декартово умножить на", "cross")),
+            Construct::Argument => self.say(self.ru_en("от", "of")),
+            Construct::Equilibrium => self.operator('⇌'),
+            Construct::ReverseArrow => {}
+        }
+    }
+
+    fn operator(&mut self, c: char) {
+        let name = reaction_sign_name(c, self.options.language).filter(|_| self.chemistry);
+        match name.or_else(|| operator_name(c, self.options.language)) {
+            Some(name) => self.say(name),
+            None => self.letter(c),
+        }
+    }
+
+    fn fraction(&mut self, e: &MathElement) {
+        let (num, denom) = (&e.children[0], &e.children[1]);
+        let simple = num.is_simple() && denom.is_simple();
+        if simple && !self.verbose() {
+            self.element(num);
+            self.say(self.ru_en("на", "over"));
+            self.element(denom);
+        } else if self.brief() {
+            self.say(self.ru_en("дробь", "the fraction"));
+            self.element(num);
+            self.say(self.ru_en("на", "over"));
+            self.element(denom);
+            self.say(self.ru_en("конец дроби", "end fraction"));
+        } else {
+            match self.options.language {
+                Language::Russian => {
+                    self.say("дробь");
+                    self.pause();
+                    self.say("числитель");
+                }
+                Language::English => self.say("the fraction with numerator"),
+            }
+            self.element(num);
+            self.pause();
+            self.say(self.ru_en("знаменатель", "and denominator"));
+            self.element(denom);
+            self.end(!denom.is_simple(), "конец дроби", "end fraction");
+        }
+    }
+
+    fn subscript(&mut self, base: &MathElement, sub: &MathElement) {
+        self.element(base);
+        if base.element == ElementType::Ident && base.text == "log" {
+            self.say(self.ru_en("по основанию", "base"));
+            self.element(sub);
+        } else if (self.brief() || self.chemistry) && sub.is_integer() {
+            self.element(sub);
+        } else {
+            self.say(self.ru_en("с индексом", "sub"));
+            self.element(sub);
+            self.end(!sub.is_simple(), "конец индекса", "end subscript");
+        }
+    }
+
+    fn superscript(&mut self, sup: &MathElement) {
+        if self.chemistry && sup.charge().is_some() {
+            self.say(self.ru_en("заряд", "charge"));
+            self.element(sup);
+        } else if sup.element == ElementType::Operator && sup.text == "∘" {
+            self.operator('°');
+        } else if sup.is_operators() {
+            self.element(sup);
+        } else if sup.is_integer() && sup.text == "2" {
+            self.say(self.ru_en("в квадрате", "squared"));
+        } else if sup.is_integer() && sup.text == "3" {
+            self.say(self.ru_en("в кубе", "cubed"));
+        } else {
+            self.say(self.ru_en("в степени", "to the power of"));
+            self.element(sup);
+            self.end(!sup.is_simple(), "конец степени", "end exponent");
+        }
+    }
+
+    fn script(&mut self, base: &MathElement, script: &MathElement, over: bool) {
+        let accent = match script.element {
+            ElementType::Operator if script.text.chars().count() == 1 => {
+                accent_name(script.text.chars().next().unwrap(), self.options.language)
+            }
+            _ => None,
+        };
+        match accent {
+            Some((name, true)) => {
+                self.say(name);
+                self.element(base);
+            }
+            Some((name, false)) => {
+                self.element(base);
+                self.say(name);
+            }
+            None => {
+                self.element(base);
+                self.pause();
+                self.say(if over {
+                    self.ru_en("сверху", "with above")
+                } else {
+                    self.ru_en("снизу", "with below")
+                });
+                self.element(script);
+                self.pause();
+            }
+        }
+    }
+
+    // Sums, integrals and limits are read with their bounds: "сумма от i равно 1 до n".
+    fn large_operator(&mut self, scripts: &Scripts) {
+        self.element(scripts.base);
+        let is_limit = scripts.base.element != ElementType::Operator;
+        match (scripts.lower, scripts.upper) {
+            (Some(lower), _) if is_limit => {
+                self.say(self.ru_en("при", "as"));
+                self.element(lower);
+            }
+            (Some(lower), None) => {
+                self.say(self.ru_en("по", "over"));
+                self.element(lower);
+            }
+            (lower, upper) => {
+                if let Some(lower) = lower {
+                    self.say(self.ru_en("от", "from"));
+                    self.element(lower);
+                }
+                if let Some(upper) = upper {
+                    self.say(self.ru_en("до", "to"));
+                    self.element(upper);
+                }
+            }
+        }
+        self.pause();
+    }
+
+    fn finish(self) -> String {
+        self.words.join(" ").trim_end_matches(',').to_string()
+    }
+}
+
+pub fn speak(ml: &str, options: SpeechOptions) -> Result<String, RenderError> {
+    let (normalized, _) = normalize_source(ml);
+    let document = roxmltree::Document::parse(&normalized)?;
+    let root = document.root_element();
+    if root.tag_name().name() != "math" {
+        return Err(RenderError::NotMathML(root.tag_name().name().to_string()));
+    }
+    let mut r = SpeechRenderer {
+        options,
+        words: Vec::new(),
+        points: false,
+        chemistry: false,
+    };
+    if let Some(math) = MathElement::from_xml(root) {
+        r.chemistry = options.chemistry.applies(&math);
+        r.element(&math);
+    }
+    Ok(r.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The formula spoken at the brief, medium and verbose levels.
+    fn speak_all(ml: &str, language: Language) -> [String; 3] {
+        [Verbosity::Brief, Verbosity::Medium, Verbosity::Verbose].map(|verbosity| {
+            let options = SpeechOptions {
+                language,
+                verbosity,
+                ..Default::default()
+            };
+            speak(&format!("<math>{}</math>", ml), options).unwrap()
+        })
+    }
+
+    fn ru(ml: &str) -> [String; 3] {
+        speak_all(ml, Language::Russian)
+    }
+
+    fn en(ml: &str) -> [String; 3] {
+        speak_all(ml, Language::English)
+    }
+
+    #[test]
+    fn fractions() {
+        let half = "<mfrac><mn>1</mn><mn>2</mn></mfrac>";
+        assert_eq!(
+            ru(half),
+            [
+                "один на два",
+                "один на два",
+                "дробь, числитель один, знаменатель два, конец дроби"
+            ]
+        );
+        assert_eq!(
+            en(half),
+            [
+                "one over two",
+                "one over two",
+                "the fraction with numerator one, and denominator two, end fraction"
+            ]
+        );
+        // The end is announced when the denominator doesn't make it clear.
+        let compound = "<mfrac><mi>a</mi><mrow><mi>b</mi><mo>+</mo><mi>c</mi></mrow></mfrac>";
+        assert_eq!(
+            ru(compound),
+            [
+                "дробь а на бэ плюс цэ конец дроби",
+                "дробь, числитель а, знаменатель бэ плюс цэ, конец дроби",
+                "дробь, числитель а, знаменатель бэ плюс цэ, конец дроби"
+            ]
+        );
+        assert_eq!(
+            en(compound),
+            [
+                "the fraction a over b plus c end fraction",
+                "the fraction with numerator a, and denominator b plus c, end fraction",
+                "the fraction with numerator a, and denominator b plus c, end fraction"
+            ]
+        );
+        let compound = "<mfrac><mrow><mi>a</mi><mo>+</mo><mi>b</mi></mrow><mi>c</mi></mfrac>";
+        assert_eq!(
+            ru(compound),
+            [
+                "дробь а плюс бэ на цэ конец дроби",
+                "дробь, числитель а плюс бэ, знаменатель цэ",
+                "дробь, числитель а плюс бэ, знаменатель цэ, конец дроби"
+            ]
+        );
+        assert_eq!(
+            en(compound)[1],
+            "the fraction with numerator a plus b, and denominator c"
+        );
+    }
+
+    #[test]
+    fn roots() {
+        let sqrt = "<msqrt><mi>x</mi></msqrt>";
+        assert_eq!(
+            ru(sqrt),
+            [
+                "корень из икс",
+                "корень из икс",
+                "квадратный корень из икс, конец корня"
+            ]
+        );
+        assert_eq!(
+            en(sqrt),
+            [
+                "the square root of x",
+                "the square root of x",
+                "the square root of x, end root"
+            ]
+        );
+        let sqrt = "<msqrt><mi>x</mi><mo>+</mo><mn>1</mn></msqrt>";
+        assert_eq!(
+            ru(sqrt),
+            [
+                "корень из икс плюс один",
+                "корень из икс плюс один, конец корня",
+                "квадратный корень из икс плюс один, конец корня"
+            ]
+        );
+        assert_eq!(
+            en(sqrt),
+            [
+                "the square root of x plus one",
+                "the square root of x plus one, end root",
+                "the square root of x plus one, end root"
+            ]
+        );
+        // The cube root has its own name, except where every index is spelled out.
+        let cube = "<mroot><mi>x</mi><mn>3</mn></mroot>";
+        assert_eq!(
+            ru(cube),
+            [
+                "кубический корень из икс",
+                "кубический корень из икс",
+                "корень степени три из икс, конец корня"
+            ]
+        );
+        assert_eq!(
+            en(cube),
+            [
+                "the cube root of x",
+                "the cube root of x",
+                "the root of index three of x, end root"
+            ]
+        );
+        let root = "<mroot><mi>x</mi><mn>5</mn></mroot>";
+        assert_eq!(
+            ru(root),
+            [
+                "корень степени пять из икс",
+                "корень степени пять из икс",
+                "корень степени пять из икс, конец корня"
+            ]
+        );
+        assert_eq!(
+            en(root),
+            [
+                "the root of index five of x",
+                "the root of index five of x",
+                "the root of index five of x, end root"
+            ]
+        );
+    }
+
+    #[test]
+    fn powers() {
+        assert_eq!(
+            ru("<msup><mi>x</mi><mn>2</mn></msup>"),
+            ["икс в квадрате"; 3]
+        );
+        assert_eq!(en("<msup><mi>x</mi><mn>2</mn></msup>"), ["x squared"; 3]);
+        assert_eq!(ru("<msup><mi>x</mi><mn>3</mn></msup>"), ["икс в кубе"; 3]);
+        assert_eq!(en("<msup><mi>x</mi><mn>3</mn></msup>"), ["x cubed"; 3]);
+        let power = "<msup><mi>x</mi><mi>n</mi></msup>";
+        assert_eq!(
+            ru(power),
+            [
+                "икс в степени эн",
+                "икс в степени эн",
+                "икс в степени эн, конец степени"
+            ]
+        );
+        assert_eq!(
+            en(power),
+            [
+                "x to the power of n",
+                "x to the power of n",
+                "x to the power of n, end exponent"
+            ]
+        );
+        let power = "<msup><mi>x</mi><mrow><mi>n</mi><mo>+</mo><mn>1</mn></mrow></msup>";
+        assert_eq!(
+            ru(power),
+            [
+                "икс в степени эн плюс один",
+                "икс в степени эн плюс один, конец степени",
+                "икс в степени эн плюс один, конец степени"
+            ]
+        );
+        assert_eq!(
+            en(power),
+            [
+                "x to the power of n plus one",
+                "x to the power of n plus one, end exponent",
+                "x to the power of n plus one, end exponent"
+            ]
+        );
+        let indexed = "<msubsup><mi>x</mi><mn>1</mn><mn>2</mn></msubsup>";
+        assert_eq!(
+            ru(indexed),
+            [
+                "икс один в квадрате",
+                "икс с индексом один в квадрате",
+                "икс с индексом один, конец индекса, в квадрате"
+            ]
+        );
+    }
+
+    #[test]
+    fn log_bases() {
+        let log = "<msub><mi>log</mi><mn>2</mn></msub><mi>x</mi>";
+        assert_eq!(ru(log), ["логарифм по основанию два икс"; 3]);
+        assert_eq!(en(log), ["log base two x"; 3]);
+        let log = "<msub><mi>log</mi><mi>a</mi></msub><mi>b</mi>";
+        assert_eq!(ru(log), ["логарифм по основанию а бэ"; 3]);
+        assert_eq!(en(log), ["log base a b"; 3]);
+    }
+
+    #[test]
+    fn sums_and_limits() {
+        let sum = "<munderover><mo>∑</mo><mrow><mi>i</mi><mo>=</mo><mn>1</mn></mrow>\
+            <mi>n</mi></munderover><mi>i</mi>";
+        assert_eq!(ru(sum), ["сумма от и равно один до эн, и"; 3]);
+        assert_eq!(en(sum), ["the sum from i equals one to n, i"; 3]);
+        // The limits of an integral are read the same way wherever they are placed.
+        let integral = "<msubsup><mo>∫</mo><mn>0</mn><mn>1</mn></msubsup><mi>x</mi>";
+        assert_eq!(ru(integral), ["интеграл от ноль до один, икс"; 3]);
+        assert_eq!(en(integral), ["the integral from zero to one, x"; 3]);
+        let limit = "<munder><mi>lim</mi><mrow><mi>x</mi><mo>→</mo><mn>0</mn></mrow></munder>\
+            <mi>f</mi>";
+        assert_eq!(ru(limit), ["предел при икс стремится к ноль, эф"; 3]);
+        assert_eq!(en(limit), ["limit as x approaches zero, f"; 3]);
+        let max = "<msub><mi>max</mi><mi>x</mi></msub><mi>f</mi>";
+        assert_eq!(ru(max), ["максимум при икс, эф"; 3]);
+        assert_eq!(en(max), ["maximum as x, f"; 3]);
+    }
+
+    #[test]
+    fn intervals_and_sets() {
+        let closed = "<mo>[</mo><mi>a</mi><mo>;</mo><mi>b</mi><mo>]</mo>";
+        assert_eq!(ru(closed), ["отрезок от а до бэ"; 3]);
+        assert_eq!(en(closed), ["the closed interval from a to b"; 3]);
+        let open = "<mo>(</mo><mi>a</mi><mo>;</mo><mi>b</mi><mo>)</mo>";
+        assert_eq!(ru(open), ["интервал от а до бэ"; 3]);
+        assert_eq!(en(open), ["the open interval from a to b"; 3]);
+        let half_open = "<mo>[</mo><mn>0</mn><mo>;</mo><mn>1</mn><mo>)</mo>";
+        assert_eq!(ru(half_open), ["полуинтервал от ноль до один"; 3]);
+        assert_eq!(
+            en(half_open),
+            ["the half-open interval from zero to one"; 3]
+        );
+        let set = "<mo>{</mo><mi>x</mi><mo>|</mo><mi>x</mi><mo>&gt;</mo><mn>0</mn><mo>}</mo>";
+        assert_eq!(ru(set), ["множество икс таких, что икс больше ноль"; 3]);
+        assert_eq!(
+            en(set),
+            ["the set of x such that x is greater than zero"; 3]
+        );
+    }
+
+    #[test]
+    fn fractional_parts() {
+        // Leading zeros of the fractional part are read one by one before the rest of it.
+        assert_eq!(ru("<mn>0,05</mn>"), ["ноль запятая ноль пять"; 3]);
+        assert_eq!(ru("<mn>0,005</mn>"), ["ноль запятая ноль ноль пять"; 3]);
+        assert_eq!(ru("<mn>2,0</mn>"), ["два запятая ноль"; 3]);
+        assert_eq!(ru("<mn>3,14</mn>"), ["три запятая четырнадцать"; 3]);
+        assert_eq!(ru("<mn>0.5</mn>"), ["ноль запятая пять"; 3]);
+        // English reads every digit after the point.
+        assert_eq!(en("<mn>0,05</mn>"), ["zero point zero five"; 3]);
+        assert_eq!(en("<mn>3.14</mn>"), ["three point one four"; 3]);
+    }
+
+    #[test]
+    fn scripts_over_figures() {
+        let segment = "<mover><mrow><mi>A</mi><mi>B</mi></mrow><mo>‾</mo></mover>";
+        assert_eq!(ru(segment)[1], "отрезок а бэ");
+        assert_eq!(en(segment)[1], "segment a b");
+        assert_eq!(ru("<mover><mi>x</mi><mo>→</mo></mover>")[1], "вектор икс");
+        assert_eq!(
+            en("<munderover><mi>x</mi><mi>y</mi><mi>z</mi></munderover>")[1],
+            "x, with below y, and above z"
+        );
+        assert_eq!(
+            ru("<msubsup><mo>|</mo><mn>0</mn><mn>1</mn></msubsup>")[1],
+            "в пределах от ноль до один"
+        );
+    }
+}
@@ -1,9 +1,61 @@
+use std::{collections::HashMap, sync::RwLock};
+
 use brl::{braille, BrailleString};
+use once_cell::sync::Lazy;
+
+use crate::{
+    constants::CAPITAL_LATIN_PREFIX, letters::Classifier, literary, math_element::SubscriptType,
+};
+
+static CUSTOM_SYMBOLS: Lazy<RwLock<HashMap<char, BrailleString>>> = Lazy::new(Default::default);
+static CUSTOM_FUNCTIONS: Lazy<RwLock<HashMap<String, BrailleString>>> = Lazy::new(Default::default);
+
+// Letterlike symbols and the reserved mathematical alphanumeric code points they stand for.
+const LETTERLIKE: &[(char, char)] = &[
+    ('ℎ', '𝑕'),
+    ('ℬ', '𝒝'),
+    ('ℰ', '𝒠'),
+    ('ℱ', '𝒡'),
+    ('ℋ', '𝒣'),
+    ('ℐ', '𝒤'),
+    ('ℒ', '𝒧'),
+    ('ℳ', '𝒨'),
+    ('ℛ', '𝒭'),
+    ('ℯ', '𝒺'),
+    ('ℊ', '𝒼'),
+    ('ℴ', '𝓄'),
+    ('ℭ', '𝔆'),
+    ('ℌ', '𝔋'),
+    ('ℑ', '𝔌'),
+    ('ℜ', '𝔕'),
+    ('ℨ', '𝔝'),
+    ('ℂ', '𝔺'),
+    ('ℍ', '𝔿'),
+    ('ℕ', '𝕅'),
+    ('ℙ', '𝕇'),
+    ('ℚ', '𝕈'),
+    ('ℝ', '𝕉'),
+    ('ℤ', '𝕑'),
+];
 
-use crate::math_element::SubscriptType;
+fn letterlike_hole(c: char) -> Option<char> {
+    LETTERLIKE
+        .iter()
+        .find(|&&(l, _)| l == c)
+        .map(|&(_, hole)| hole)
+}
+
+// The letter that is encoded in place of a reserved mathematical alphanumeric code point.
+pub(crate) fn letterlike(hole: char) -> Option<char> {
+    LETTERLIKE
+        .iter()
+        .find(|&&(_, h)| h == hole)
+        .map(|&(l, _)| l)
+}
 
 pub fn normalize(c: char) -> Option<char> {
-    if (0x2061..=0x2064).contains(&(c as u32)) {
+    // Function application is kept, it ties the argument to the name of the function.
+    if (0x2062..=0x2064).contains(&(c as u32)) {
         None
     } else {
         Some(match c {
@@ -14,107 +66,358 @@ pub fn normalize(c: char) -> Option<char> {
             'ϑ' => 'θ',
             '𝛝' => '𝛉',
             '−' => '-',
+            '⁄' => '/',
+            'ⅆ' => 'd',
+            'ⅇ' => 'e',
+            'ⅈ' => 'i',
             '⩾' => '≥',
             '⩽' => '≤',
-
-            'ℎ' => '𝑕',
-            'ℬ' => '𝒝',
-            'ℰ' => '𝒠',
-            'ℱ' => '𝒡',
-            'ℋ' => '𝒣',
-            'ℐ' => '𝒤',
-            'ℒ' => '𝒧',
-            'ℳ' => '𝒨',
-            'ℛ' => '𝒭',
-            'ℯ' => '𝒺',
-            'ℊ' => '𝒼',
-            'ℴ' => '𝓄',
-            'ℭ' => '𝔆',
-            'ℌ' => '𝔋',
-            'ℑ' => '𝔌',
-            'ℜ' => '𝔕',
-            'ℨ' => '𝔝',
-            'ℂ' => '𝔺',
-            'ℍ' => '𝔿',
-            'ℕ' => '𝕅',
-            'ℙ' => '𝕇',
-            'ℚ' => '𝕈',
-            'ℝ' => '𝕉',
-            'ℤ' => '𝕑',
-            other => other,
+            '≦' => '≤',
+            '≧' => '≥',
+            '⋯' => '…',
+            '⟂' => '⊥',
+            '∡' => '∠',
+            '⌢' | '⏜' => '⌒',
+            '¯' => '‾',
+            other => letterlike_hole(other).unwrap_or(other),
         })
     }
 }
 
+// Signs of geometric figures, which are followed by the names of their points, as in ∠ABC.
+pub fn is_figure_sign(c: char) -> bool {
+    matches!(c, '∠' | '△' | '⌒')
+}
+
+// Abbreviations of units of measurement, which are separated from the number before them.
+const UNITS: &[&str] = &[
+    "мм", "см", "дм", "м", "км", "мг", "г", "кг", "т", "мс", "с", "мин", "ч", "сут", "мл", "л",
+    "Н", "Дж", "кДж", "Вт", "кВт", "Па", "кПа", "В", "А", "мА", "Ом", "Гц", "К", "моль", "кд",
+    "Кл", "Тл", "Ф", "эВ", "mm", "cm", "dm", "km", "mg", "kg", "ms", "min", "mL", "ml", "J",
+    "kJ", "W", "kW", "Pa", "kPa", "mA", "Ω", "Hz", "mol", "cd", "eV", "m", "g", "s", "h", "l",
+    "N", "V", "A", "K",
+];
+
+pub fn is_unit(text: &str) -> bool {
+    !text.is_empty()
+        && text
+            .split(['/', '⋅', '·'])
+            .all(|u| UNITS.contains(&u.trim_end_matches(['²', '³'])))
+}
+
+pub const SPECIAL_FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "tg", "cot", "ctg", "sec", "cosec", "csc", "arcsin", "arccos", "arctan",
+    "arctg", "arccot", "arcctg", "sinh", "sh", "cosh", "ch", "tanh", "th", "coth", "cth", "log",
+    "ln", "lg", "exp", "lim", "max", "min", "sup", "inf", "det", "deg", "gcd", "НОД", "НОК", "sgn",
+    "Re", "Im", "arg", "rank", "dim", "ker", "mod",
+];
+
+// The function sign followed by the letters of the name, capital Latin letters with their prefix.
+fn spelled_function(f: &str) -> BrailleString {
+    let mut res = braille!(1246);
+    for c in f.chars() {
+        if let Some(l) = Classifier::classify(c) {
+            if l.1.capital {
+                res += CAPITAL_LATIN_PREFIX;
+            }
+            res += l.get_rendering();
+        } else if let Some(l) = literary::cyrillic_letter(c) {
+            res += l;
+        }
+    }
+    res
+}
+
+pub fn builtin_function(f: &str) -> Option<BrailleString> {
+    Some(match f {
+        // Trigonometric functions are abbreviated by the Russian names, as in ctg.
+        "sin" => braille!(1246, 234),
+        "cos" => braille!(1246, 13),
+        "tan" | "tg" => braille!(1246, 2345),
+        "cot" | "ctg" => braille!(1246,13,2345),
+        "sec" => braille!(1246, 234, 15, 13),
+        "cosec" | "csc" => braille!(1246, 13, 234, 15, 13),
+        "arcsin" => braille!(1246, 1, 234),
+        "arccos" => braille!(1246, 1, 13),
+        "arctan" | "arctg" => braille!(1246, 1, 2345),
+        "arccot" | "arcctg" => braille!(1246, 1, 13, 2345),
+        "sinh" | "sh" => braille!(1246, 234, 125),
+        "cosh" | "ch" => braille!(1246, 13, 125),
+        "tanh" | "th" => braille!(1246, 2345, 125),
+        "coth" | "cth" => braille!(1246, 13, 2345, 125),
+        "log" => braille!(1246, 123),
+        "ln" => braille!(1246,123,1345),
+        "lg" => braille!(1246,123,1245),
+        "lim" => braille!(1246, 123, 134),
+        "exp" | "max" | "min" | "sup" | "inf" | "det" | "deg" | "gcd" | "НОД" | "НОК" | "sgn"
+        | "Re" | "Im" | "arg" | "rank" | "dim" | "ker" | "mod" => spelled_function(f),
+        _ => return None,
+    })
+}
+
+// The words a builtin function is read with, in Russian and in English.
+pub(crate) fn spoken_function(f: &str) -> Option<(&'static str, &'static str)> {
+    Some(match f {
+        "sin" => ("синус", "sine"),
+        "cos" => ("косинус", "cosine"),
+        "tan" | "tg" => ("тангенс", "tangent"),
+        "cot" | "ctg" => ("котангенс", "cotangent"),
+        "sec" => ("секанс", "secant"),
+        "cosec" | "csc" => ("косеканс", "cosecant"),
+        "arcsin" => ("арксинус", "arcsine"),
+        "arccos" => ("арккосинус", "arccosine"),
+        "arctan" | "arctg" => ("арктангенс", "arctangent"),
+        "arccot" | "arcctg" => ("арккотангенс", "arccotangent"),
+        "sinh" | "sh" => ("гиперболический синус", "hyperbolic sine"),
+        "cosh" | "ch" => ("гиперболический косинус", "hyperbolic cosine"),
+        "tanh" | "th" => ("гиперболический тангенс", "hyperbolic tangent"),
+        "coth" | "cth" => ("гиперболический котангенс", "hyperbolic cotangent"),
+        "log" => ("логарифм", "log"),
+        "ln" => ("натуральный логарифм", "natural log"),
+        "lg" => ("десятичный логарифм", "common log"),
+        "exp" => ("экспонента", "exponential"),
+        "lim" => ("предел", "limit"),
+        "max" => ("максимум", "maximum"),
+        "min" => ("минимум", "minimum"),
+        "sup" => ("супремум", "supremum"),
+        "inf" => ("инфимум", "infimum"),
+        "det" => ("определитель", "determinant"),
+        "deg" => ("степень", "degree"),
+        "gcd" | "НОД" => ("наибольший общий делитель", "greatest common divisor"),
+        "НОК" => ("наименьшее общее кратное", "least common multiple"),
+        "sgn" => ("знак", "sign"),
+        "Re" => ("действительная часть", "real part"),
+        "Im" => ("мнимая часть", "imaginary part"),
+        "arg" => ("аргумент", "argument"),
+        "rank" => ("ранг", "rank"),
+        "dim" => ("размерность", "dimension"),
+        "ker" => ("ядро", "kernel"),
+        "mod" => ("по модулю", "modulo"),
+        _ => return None,
+    })
+}
+
+pub fn register_function(name: &str, s: BrailleString) {
+    CUSTOM_FUNCTIONS
+        .write()
+        .unwrap()
+        .insert(name.to_string(), s);
+}
+
 pub fn special_function(f: &str) -> Option<BrailleString> {
-    match f {
-        "sin" => Some(braille!(1246, 234)),
-        "cos" => Some(braille!(1246, 13)),
-        "tan" => Some(braille!(1246, 2345)),
-        "cot" | "ctg" => Some(braille!(1246,13,2345)),
-        "log" => Some(braille!(1246, 123)),
-        "ln" => Some(braille!(1246,123,1345)),
-        "lg" => Some(braille!(1246,123,1245)),
-        "lim" => Some(braille!(1246, 123, 134)),
+    if let Some(s) = CUSTOM_FUNCTIONS.read().unwrap().get(f) {
+        return Some(s.clone());
+    }
+    builtin_function(f)
+}
+
+// Other operator names, e.g. from \operatorname{rot}, which are spelled out after the function sign.
+// Abbreviations of units, such as kg, are not operator names.
+pub fn operator_name(f: &str) -> Option<BrailleString> {
+    special_function(f).or_else(|| {
+        (f.chars().count() > 1
+            && f.chars().all(|c| c.is_ascii_alphabetic())
+            && f.chars().any(|c| c.is_ascii_lowercase())
+            && !is_unit(f))
+        .then(|| spelled_function(f))
+    })
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub(crate) enum LargeOperator {
+    // Limits are written as indices.
+    Integral,
+    // Limits are written under and over the sign.
+    Sum,
+    // Functions such as lim and max, written like sums.
+    Limit,
+}
+
+pub const LIMIT_FUNCTIONS: &[&str] = &["lim", "max", "min", "sup", "inf"];
+
+pub(crate) fn large_operator(c: char) -> Option<LargeOperator> {
+    match c {
+        '∫' | '∬' | '∭' | '∮' | '∯' | '∰' => Some(LargeOperator::Integral),
+        '∑' | '∏' | '∐' | '⋃' | '⋂' | '⋀' | '⋁' => Some(LargeOperator::Sum),
         _ => None,
     }
 }
 
 pub (crate) fn has_affinity(c: char, script: SubscriptType) -> bool {
     match c {
-        '′' | '+' | '-' | '∗' if script == SubscriptType::Sup => true,
+        '′' | '″' | '‴' | '+' | '-' | '∗' if script == SubscriptType::Sup => true,
         '~' | '˙' | '^' if script == SubscriptType::Over => true,
         _ => false
     }
 }
 
 
+pub fn register_symbol(c: char, s: BrailleString) {
+    CUSTOM_SYMBOLS.write().unwrap().insert(c, s);
+}
+
+pub fn clear_custom_symbols() {
+    CUSTOM_SYMBOLS.write().unwrap().clear();
+}
+
+pub fn clear_custom_functions() {
+    CUSTOM_FUNCTIONS.write().unwrap().clear();
+}
+
 pub fn braille_symbol(c: char) -> BrailleString {
+    if let Some(s) = CUSTOM_SYMBOLS.read().unwrap().get(&c) {
+        return s.clone();
+    }
+    builtin_symbol(c).unwrap_or_else(|| {
+        log::warn!("Unrecognized symbol: {}", c);
+        braille!(123456)
+    })
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) enum SymbolClass {
+    // Relations and arrows, which are always preceded by a blank, a = b.
+    Relation,
+    // Binary operations, preceded by a blank only after an operand, so that -a stays together.
+    Operation,
+    Other,
+}
+
+pub(crate) fn symbol_class(c: char) -> SymbolClass {
     match c {
+        '=' | '≠' | '<' | '>' | '≤' | '≥' | '≮' | '≯' | '≰' | '≱' | '≪' | '≫' | '≈' | '≃' | '≅'
+        | '∼' | '≡' | '∝' | '∈' | '∉' | '∋' | '⊂' | '⊃' | '⊆' | '⊇' | '⊄' | '⊅' | '∥' | '⊥'
+        | '∣' | '∤' | '→' | '←' | '↔' | '⇒' | '⇐' | '⇔' | '⟶' | '⟵' | '⟷' | '⟹' | '⟸' | '⟺'
+        | '↦' | '⟼' | '↑' | '↓' | '↗' | '↘' | '↖' | '↙' | '⇀' | '⇁' | '↼' | '↽' | '⇌' | '⇋' => {
+            SymbolClass::Relation
+        }
+        '+' | '-' | '±' | '∓' | '∪' | '∩' | '∖' | '∆' | '⊕' | '∧' | '∨' => {
+            SymbolClass::Operation
+        }
+        _ => SymbolClass::Other,
+    }
+}
+
+pub fn builtin_symbol(c: char) -> Option<BrailleString> {
+    Some(match c {
         '+' => braille!(235),
         '-' => braille!(36),
         '±' => braille!(235, 36),
         '∓' => braille!(36, 235),
         '×' => braille!(236),
-        '⋅' => braille!(3),
+        '÷' | '∶' => braille!(256),
+        '/' => braille!(34),
+        '∘' => braille!(5, 356),
+        '⋅' | '·' => braille!(3),
 
-        '=' => braille!(0, 2356),
-        '>' => braille!(0, 135, 0),
-        '<' => braille!(0, 246, 0),
-        '≥' => braille!(0, 135, 2356),
-        '≤' => braille!(0, 246, 2356),
+        '=' => braille!(2356),
+        '≠' => braille!(45, 2356),
+        '>' => braille!(135),
+        '<' => braille!(246),
+        '≥' => braille!(135, 2356),
+        '≤' => braille!(246, 2356),
+        '≯' => braille!(45, 135),
+        '≮' => braille!(45, 246),
+        '≱' => braille!(45, 135, 2356),
+        '≰' => braille!(45, 246, 2356),
+        '≫' => braille!(135, 135),
+        '≪' => braille!(246, 246),
+        '≈' => braille!(26, 26),
+        '≃' => braille!(26, 36),
+        '∝' => braille!(5, 1245),
 
         ',' => braille!(6, 2),
         '…' => braille!(6, 3),
         ':' => braille!(6, 25,0),
+        '.' => braille!(6, 256),
+        ';' => braille!(6, 23),
         '!' => braille!(6,235),
 
         '∑' => braille!(456, 234),
         '∏' => braille!(456, 1234),
+        '∐' => braille!(4, 456, 1234),
+        '⋃' => braille!(456, 56, 356),
+        '⋂' => braille!(456, 56, 256),
+        '⋀' => braille!(456, 56, 236),
+        '⋁' => braille!(456, 56, 35),
 
         '∫' => braille!(2346),
         '∬' => braille!(2346, 2346),
         '∭' => braille!(2346, 2346, 2346),
+        '∮' => braille!(2346, 356),
+        '∯' => braille!(2346, 2346, 356),
+        '∰' => braille!(2346, 2346, 2346, 356),
         '∂' => braille!(1456),
         '′' => braille!(35),
+        '″' => braille!(35, 35),
+        '‴' => braille!(35, 35, 35),
+        '°' => braille!(34, 356),
+        '℃' => braille!(34, 356, 46, 14),
+        '%' => braille!(3456, 245, 356),
+        '‰' => braille!(3456, 245, 356, 356),
 
-'∈' => braille!(0,5,246,0),
-'∉' => braille!(0,45,246,0),
-'⊂' => braille!(0,12346,0),
+'∈' => braille!(5,246),
+'∉' => braille!(45,246),
+'∋' => braille!(2,135),
+'⊂' => braille!(12346),
+'⊃' => braille!(13456),
+'⊆' => braille!(12346,2356),
+'⊇' => braille!(13456,2356),
+'⊄' => braille!(45,12346),
+'⊅' => braille!(45,13456),
 '∅' => braille!(4,356),
-'∪' => braille!(0,56,356),
-'∩' => braille!(0,56,256),
-'∖' => braille!(56,256),
+'∪' => braille!(56,356),
+'∩' => braille!(56,256),
+'∖' => braille!(56,36),
+'∆' => braille!(56,145),
+'⊕' => braille!(56,235),
+'ℵ' => braille!(45,1),
 
-'∧' => braille!(0,56,236),
-'∨' => braille!(0,56,35),
+'∧' => braille!(56,236),
+'∨' => braille!(56,35),
 '¬' => braille!(26),
 '∀' => braille!(1246,3),
 '∃' => braille!(1246,26),
+'∄' => braille!(45,1246,26),
+'≡' => braille!(2356,2356),
+
+
+        '∞' => braille!(3456, 123456),
 
+        '→' => braille!(25, 135),
+        '←' => braille!(246,25),
+        '↔' => braille!(246, 25, 135),
+        '⇒' => braille!(2356, 135),
+        '⇐' => braille!(246, 2356),
+        '⇔' => braille!(246, 2356, 135),
+        '⟶' => braille!(25, 25, 135),
+        '⟵' => braille!(246, 25, 25),
+        '⟷' => braille!(246, 25, 25, 135),
+        '⟹' => braille!(2356, 2356, 135),
+        '⟸' => braille!(246, 2356, 2356),
+        '⟺' => braille!(246, 2356, 2356, 135),
+        '↦' => braille!(456, 25, 135),
+        '⟼' => braille!(456, 25, 25, 135),
+        '↑' => braille!(45, 25, 135),
+        '↓' => braille!(56, 25, 135),
+        '↗' => braille!(4, 25, 135),
+        '↘' => braille!(6, 25, 135),
+        '↖' => braille!(4, 246, 25),
+        '↙' => braille!(6, 246, 25),
+        '⇀' => braille!(25, 4),
+        '⇁' => braille!(25, 6),
+        '↼' => braille!(4, 25),
+        '↽' => braille!(6, 25),
+        '⇌' => braille!(6, 25, 25, 4),
+        '⇋' => braille!(4, 25, 25, 6),
 
-        '→' => braille!(0, 25, 135),
-        '←' => braille!(0,246,25),
+'∠' => braille!(456,246),
+'△' => braille!(456,2345),
+'⌒' => braille!(456,126),
+'∥' => braille!(456,456),
+'⊥' => braille!(456,36),
+'∼' => braille!(26),
+'≅' => braille!(26,2356),
 
 '‾' => braille!(25),
 '^' => braille!(256),
@@ -125,12 +428,26 @@ pub fn braille_symbol(c: char) -> BrailleString {
         '(' => braille!(126),
         ')' => braille!(345),
         '|' => braille!(456),
+        '∣' => braille!(456),
+        '∤' => braille!(45, 456),
         '[' => braille!(12356),
         ']' => braille!(23456),
+        '{' => braille!(46, 126),
+        '}' => braille!(46, 345),
+
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        other => {
-            log::warn!("Unrecognized symbol: {}", other);
-            braille!(123456)
+    #[test]
+    fn special_functions_are_rendered_and_spoken() {
+        for &f in SPECIAL_FUNCTIONS {
+            assert!(builtin_function(f).is_some(), "{} has no braille", f);
+            assert!(spoken_function(f).is_some(), "{} has no spoken name", f);
         }
     }
 }
@@ -1,10 +1,17 @@
-use crate::{letters::Classifier, math_element::ElementType};
+use crate::{letters::Classifier, math_element::ElementType, RenderOptions, Span};
 use brl::BrailleString;
 
 pub(crate) trait Renderer {
     fn write_string(&mut self, s: BrailleString, source_type: ElementType);
+    fn options(&self) -> RenderOptions;
     fn get_last_element_type(&self) -> Option<ElementType>;
     fn get_last_classifier(&self) -> Option<Classifier>;
     fn set_last_classifier(&mut self, cls: Option<Classifier>);
+    fn get_open_number(&self) -> bool;
+    fn set_open_number(&mut self, open: bool);
+    fn get_open_function(&self) -> bool;
+    fn set_open_function(&mut self, open: bool);
+    fn position(&self) -> usize;
+    fn push_span(&mut self, span: Span);
     fn finish(self) -> BrailleString;
 }
@@ -0,0 +1,203 @@
+use std::fmt::Write;
+
+use brl::BrailleString;
+use roxmltree::{Document, Node, ParsingOptions};
+
+use crate::{normalize_source, render_node, RenderError, RenderOptions};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MathMode {
+    // Replace every formula with a span containing its braille rendering.
+    Replace,
+    // Keep the formulas and store the rendering in the aria-braillelabel attribute.
+    Annotate,
+}
+
+pub struct Transcription {
+    pub document: String,
+    pub rendered: usize,
+    pub failed: usize,
+}
+
+fn escape_attribute(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+}
+
+// The formula is cut out of the document, so the namespaces declared on its ancestors have to be redeclared.
+fn render_math(
+    node: Node,
+    source: &str,
+    options: RenderOptions,
+) -> Result<BrailleString, RenderError> {
+    let mut snippet = String::from("<rbmath");
+    for ns in node.namespaces() {
+        match ns.name() {
+            Some("xml") => Ok(()),
+            Some(prefix) => write!(
+                snippet,
+                " xmlns:{}=\"{}\"",
+                prefix,
+                escape_attribute(ns.uri())
+            ),
+            None => write!(snippet, " xmlns=\"{}\"", escape_attribute(ns.uri())),
+        }
+        .unwrap();
+    }
+    snippet += ">";
+    snippet += &source[node.range()];
+    snippet += "</rbmath>";
+    let (normalized, _) = normalize_source(&snippet);
+    let document = Document::parse(&normalized)?;
+    let math = document
+        .root_element()
+        .first_element_child()
+        .ok_or_else(|| RenderError::NotMathML(String::new()))?;
+    Ok(render_node(math, options).0)
+}
+
+fn tag_name_end(source: &str, start: usize) -> usize {
+    source[start + 1..]
+        .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .map_or(source.len(), |i| start + 1 + i)
+}
+
+// Renders every top-level math element of an XHTML document. Formulas that fail to render are left untouched.
+pub fn transcribe(
+    source: &str,
+    mode: MathMode,
+    options: RenderOptions,
+) -> Result<Transcription, RenderError> {
+    let parsing_options = ParsingOptions {
+        allow_dtd: true,
+        ..ParsingOptions::default()
+    };
+    let document = Document::parse_with_options(source, parsing_options)?;
+    let is_math = |n: &Node| n.is_element() && n.tag_name().name() == "math";
+    let mut res = Transcription {
+        document: String::with_capacity(source.len()),
+        rendered: 0,
+        failed: 0,
+    };
+    let mut last = 0;
+    for node in document
+        .descendants()
+        .filter(|n| is_math(n) && !n.ancestors().skip(1).any(|a| is_math(&a)))
+    {
+        let range = node.range();
+        let braille = match render_math(node, source, options) {
+            Ok(braille) => braille,
+            Err(e) => {
+                log::error!("Failed to render formula at {}: {}", range.start, e);
+                res.failed += 1;
+                continue;
+            }
+        };
+        res.rendered += 1;
+        match mode {
+            MathMode::Replace => {
+                res.document += &source[last..range.start];
+                write!(res.document, "<span class=\"braille\">{}</span>", braille).unwrap();
+                last = range.end;
+            }
+            MathMode::Annotate if node.has_attribute("aria-braillelabel") => {}
+            MathMode::Annotate => {
+                let name_end = tag_name_end(source, range.start);
+                res.document += &source[last..name_end];
+                write!(res.document, " aria-braillelabel=\"{}\"", braille).unwrap();
+                last = name_end;
+            }
+        }
+    }
+    res.document += &source[last..];
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = "<html xmlns=\"http://www.w3.org/1999/xhtml\"><body><p>Пусть \
+        <math xmlns=\"http://www.w3.org/1998/Math/MathML\"><mi>x</mi></math>.</p></body></html>";
+
+    fn braille(ml: &str) -> String {
+        crate::render(ml).unwrap().to_string()
+    }
+
+    #[test]
+    fn replace() {
+        let res = transcribe(PAGE, MathMode::Replace, RenderOptions::default()).unwrap();
+        assert_eq!((res.rendered, res.failed), (1, 0));
+        assert_eq!(
+            res.document,
+            format!(
+                "<html xmlns=\"http://www.w3.org/1999/xhtml\"><body><p>Пусть \
+                <span class=\"braille\">{}</span>.</p></body></html>",
+                braille("<math><mi>x</mi></math>")
+            )
+        );
+    }
+
+    #[test]
+    fn annotate() {
+        let res = transcribe(PAGE, MathMode::Annotate, RenderOptions::default()).unwrap();
+        assert_eq!(res.rendered, 1);
+        assert!(res.document.contains(&format!(
+            "<math aria-braillelabel=\"{}\" xmlns=",
+            braille("<math><mi>x</mi></math>")
+        )));
+        // An existing label is kept.
+        let again =
+            transcribe(&res.document, MathMode::Annotate, RenderOptions::default()).unwrap();
+        assert_eq!(again.document, res.document);
+    }
+
+    #[test]
+    fn prefixed_namespace() {
+        let page = "<html xmlns=\"http://www.w3.org/1999/xhtml\" \
+            xmlns:m=\"http://www.w3.org/1998/Math/MathML\"><body>\
+            <m:math><m:mfrac><m:mn>1</m:mn><m:mn>2</m:mn></m:mfrac></m:math></body></html>";
+        let res = transcribe(page, MathMode::Replace, RenderOptions::default()).unwrap();
+        assert_eq!(res.rendered, 1);
+        assert!(res
+            .document
+            .contains(&braille("<math><mfrac><mn>1</mn><mn>2</mn></mfrac></math>")));
+        assert!(!res.document.contains("m:math"));
+    }
+
+    #[test]
+    fn nested_math_is_rendered_once() {
+        let page =
+            "<body><math><mi>x</mi><semantics><math><mi>y</mi></math></semantics></math></body>";
+        let res = transcribe(page, MathMode::Replace, RenderOptions::default()).unwrap();
+        assert_eq!(res.rendered, 1);
+        assert!(!res.document.contains("<math"));
+    }
+
+    #[test]
+    fn render_options() {
+        let page = "<body><math><mn>1,5</mn></math></body>";
+        let options = RenderOptions {
+            decimal_mark: crate::DecimalMark::Point,
+            ..Default::default()
+        };
+        let res = transcribe(page, MathMode::Replace, options).unwrap();
+        assert!(res.document.contains(
+            &crate::render_with_options("<math><mn>1,5</mn></math>", options)
+                .unwrap()
+                .to_string()
+        ));
+        assert!(!res.document.contains(&braille("<math><mn>1,5</mn></math>")));
+    }
+
+    #[test]
+    fn malformed_document() {
+        assert!(transcribe(
+            "<html><body><p>",
+            MathMode::Replace,
+            RenderOptions::default()
+        )
+        .is_err());
+    }
+}
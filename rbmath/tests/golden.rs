@@ -0,0 +1,120 @@
+// Compares the rendering of every formula in tests/golden with the expected dot notation next to it.
+// Run with RBMATH_BLESS=1 to write the current output as the expected one.
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use rbmath::{Chemistry, RenderOptions};
+
+const GOLDEN_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+const CONVERTOR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../convertml/convertor.js");
+
+fn tex_to_mathml(tex: &str) -> Option<Result<String, String>> {
+    let output = Command::new("node").arg(CONVERTOR).arg(tex).output().ok()?;
+    if output.status.success() {
+        Some(Ok(String::from_utf8_lossy(&output.stdout).into_owned()))
+    } else {
+        Some(Err(String::from_utf8_lossy(&output.stderr).into_owned()))
+    }
+}
+
+// Puts the expected and actual cells under each other and marks the first difference.
+fn describe_difference(expected: &str, actual: &str) -> String {
+    let expected_cells: Vec<&str> = expected.split(' ').collect();
+    let actual_cells: Vec<&str> = actual.split(' ').collect();
+    let first = expected_cells
+        .iter()
+        .zip(&actual_cells)
+        .position(|(e, a)| e != a)
+        .unwrap_or(expected_cells.len().min(actual_cells.len()));
+    let column = expected_cells[..first]
+        .iter()
+        .map(|c| c.len() + 1)
+        .sum::<usize>();
+    format!(
+        "expected: {}\n  actual: {}\n          {}^ cell {}",
+        expected,
+        actual,
+        " ".repeat(column),
+        first + 1
+    )
+}
+
+fn cases() -> Vec<PathBuf> {
+    let mut res: Vec<PathBuf> = fs::read_dir(GOLDEN_DIR)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.extension().is_some_and(|e| e == "mml" || e == "tex"))
+        .collect();
+    res.sort();
+    res
+}
+
+fn check(input: &Path, bless: bool) -> Result<(), String> {
+    let source = fs::read_to_string(input).unwrap();
+    let mathml = if input.extension().is_some_and(|e| e == "tex") {
+        match tex_to_mathml(source.trim()) {
+            Some(res) => res?,
+            None => {
+                eprintln!("Skipping {}: node is not available", input.display());
+                return Ok(());
+            }
+        }
+    } else {
+        source
+    };
+    // Formulas are rendered as convertml renders them, recognizing mhchem.
+    let options = RenderOptions {
+        chemistry: Chemistry::Auto,
+        ..Default::default()
+    };
+    let actual = rbmath::render_with_options(&mathml, options)
+        .map_err(|e| e.to_string())?
+        .to_dots();
+    let expected_path = input.with_extension("dots");
+    if bless {
+        fs::write(&expected_path, format!("{}\n", actual)).unwrap();
+        return Ok(());
+    }
+    let expected = fs::read_to_string(&expected_path).map_err(|_| {
+        format!(
+            "missing {}, run with RBMATH_BLESS=1",
+            expected_path.display()
+        )
+    })?;
+    let expected = expected.trim();
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(describe_difference(expected, &actual))
+    }
+}
+
+#[test]
+fn golden() {
+    let bless = env::var_os("RBMATH_BLESS").is_some_and(|v| v != "0");
+    let cases = cases();
+    assert!(!cases.is_empty(), "no cases in {}", GOLDEN_DIR);
+    let failures: Vec<String> = cases
+        .iter()
+        .filter_map(|case| {
+            check(case, bless).err().map(|e| {
+                let details: Vec<String> = e.lines().map(|l| format!("  {}", l)).collect();
+                format!(
+                    "{}\n{}",
+                    case.file_name().unwrap().to_string_lossy(),
+                    details.join("\n")
+                )
+            })
+        })
+        .collect();
+    assert!(
+        failures.is_empty(),
+        "{} of {} golden cases failed:\n\n{}\n",
+        failures.len(),
+        cases.len(),
+        failures.join("\n\n")
+    );
+}
@@ -0,0 +1,151 @@
+// Renders random formulas and checks that rendering never panics, terminates and produces output.
+use std::{sync::mpsc, thread, time::Duration};
+
+use proptest::prelude::*;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+const LAYOUT_TAGS: &[&str] = &[
+    "mrow",
+    "msqrt",
+    "mroot",
+    "mfrac",
+    "msub",
+    "msup",
+    "msubsup",
+    "munder",
+    "mover",
+    "munderover",
+];
+
+#[derive(Clone, Debug)]
+enum Tree {
+    Token(&'static str, String),
+    Node(&'static str, Vec<Tree>),
+}
+
+impl Tree {
+    fn write(&self, res: &mut String) {
+        match self {
+            Tree::Token(tag, text) => {
+                res.push_str(&format!("<{}>", tag));
+                for c in text.chars() {
+                    match c {
+                        '<' => res.push_str("&lt;"),
+                        '>' => res.push_str("&gt;"),
+                        '&' => res.push_str("&amp;"),
+                        c => res.push(c),
+                    }
+                }
+                res.push_str(&format!("</{}>", tag));
+            }
+            Tree::Node(tag, children) => {
+                res.push_str(&format!("<{}>", tag));
+                for c in children {
+                    c.write(res);
+                }
+                res.push_str(&format!("</{}>", tag));
+            }
+        }
+    }
+
+    fn has_tokens(&self) -> bool {
+        match self {
+            Tree::Token(..) => true,
+            Tree::Node(_, children) => children.iter().any(Tree::has_tokens),
+        }
+    }
+
+    fn to_mathml(&self) -> String {
+        let mut res = String::from("<math>");
+        self.write(&mut res);
+        res.push_str("</math>");
+        res
+    }
+}
+
+fn token() -> impl Strategy<Value = Tree> {
+    prop_oneof![
+        "[0-9]{1,5}([.,][0-9]{1,3})?".prop_map(|t| Tree::Token("mn", t)),
+        "[a-zA-Zα-ωΑ-Ω]{1,2}".prop_map(|t| Tree::Token("mi", t)),
+        prop::sample::select(&["sin", "cos", "log", "ln", "lim", "max"][..])
+            .prop_map(|t| Tree::Token("mi", t.to_string())),
+        prop::sample::select(
+            &[
+                "+", "-", "=", "<", ">", "≤", "±", "×", "⋅", "(", ")", "|", "∑", "∫", "′", "¯",
+                "^", "~"
+            ][..]
+        )
+        .prop_map(|t| Tree::Token("mo", t.to_string())),
+    ]
+}
+
+// Trees in which every element has the number of arguments MathML requires.
+fn well_formed() -> impl Strategy<Value = Tree> {
+    token().prop_recursive(6, 64, 4, |inner| {
+        let pair = (inner.clone(), inner.clone()).prop_map(|(a, b)| vec![a, b]);
+        let triple =
+            (inner.clone(), inner.clone(), inner.clone()).prop_map(|(a, b, c)| vec![a, b, c]);
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 1..5).prop_map(|c| Tree::Node("mrow", c)),
+            inner.prop_map(|c| Tree::Node("msqrt", vec![c])),
+            (
+                prop::sample::select(&["mfrac", "mroot", "msub", "msup", "munder", "mover"][..]),
+                pair
+            )
+                .prop_map(|(tag, c)| Tree::Node(tag, c)),
+            (prop::sample::select(&["msubsup", "munderover"][..]), triple)
+                .prop_map(|(tag, c)| Tree::Node(tag, c)),
+        ]
+    })
+}
+
+// Any layout element with any number of children.
+fn malformed() -> impl Strategy<Value = Tree> {
+    token().prop_recursive(6, 64, 4, |inner| {
+        (
+            prop::sample::select(LAYOUT_TAGS),
+            prop::collection::vec(inner, 0..4),
+        )
+            .prop_map(|(tag, c)| Tree::Node(tag, c))
+    })
+}
+
+// Renders on a separate thread, so a panic or an endless loop fails the test instead of stopping it.
+fn render(ml: String) -> Result<String, String> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let res = rbmath::render(&ml)
+            .map(|b| b.to_dots())
+            .map_err(|e| e.to_string());
+        let _ = sender.send(res);
+    });
+    match receiver.recv_timeout(TIMEOUT) {
+        Ok(res) => res,
+        Err(mpsc::RecvTimeoutError::Timeout) => panic!("rendering did not finish in {:?}", TIMEOUT),
+        Err(mpsc::RecvTimeoutError::Disconnected) => panic!("rendering panicked"),
+    }
+}
+
+proptest! {
+    #[test]
+    fn renders_well_formed_trees(tree in well_formed()) {
+        let dots = render(tree.to_mathml()).unwrap();
+        prop_assert!(!dots.is_empty());
+    }
+
+    #[test]
+    fn renders_malformed_trees(tree in malformed()) {
+        let dots = render(tree.to_mathml()).unwrap();
+        prop_assert!(!dots.is_empty() || !tree.has_tokens());
+    }
+
+    #[test]
+    fn renders_arbitrary_strings(s in any::<String>()) {
+        let _ = render(s);
+    }
+
+    #[test]
+    fn renders_arbitrary_math_elements(s in "(<(/)?m[a-z]{1,5}>|[a-z0-9+=<&;]){0,40}") {
+        let _ = render(format!("<math>{}</math>", s));
+    }
+}
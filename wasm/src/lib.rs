@@ -0,0 +1,99 @@
+use js_sys::{Array, Object, Reflect};
+use rbmath::{Chemistry, RenderOptions};
+use wasm_bindgen::prelude::*;
+
+fn set(target: &Object, key: &str, value: impl Into<JsValue>) -> Result<(), JsValue> {
+    Reflect::set(target, &JsValue::from_str(key), &value.into()).map(|_| ())
+}
+
+fn get_flag(options: &JsValue, key: &str) -> Result<bool, JsValue> {
+    if options.is_undefined() || options.is_null() {
+        return Ok(false);
+    }
+    Ok(Reflect::get(options, &JsValue::from_str(key))?.is_truthy())
+}
+
+fn get_string(options: &JsValue, key: &str) -> Result<Option<String>, JsValue> {
+    if options.is_undefined() || options.is_null() {
+        return Ok(None);
+    }
+    let value = Reflect::get(options, &JsValue::from_str(key))?;
+    if value.is_undefined() || value.is_null() {
+        return Ok(None);
+    }
+    match value.as_string() {
+        Some(s) => Ok(Some(s)),
+        None => Err(JsError::new(&format!("{} must be a string", key)).into()),
+    }
+}
+
+// Chemistry defaults to auto, as in convertml and the Python module.
+fn render_options(
+    decimal_mark: Option<&str>,
+    chemistry: Option<&str>,
+) -> Result<RenderOptions, String> {
+    Ok(RenderOptions {
+        decimal_mark: decimal_mark
+            .map(str::parse)
+            .transpose()?
+            .unwrap_or_default(),
+        chemistry: chemistry
+            .map(str::parse)
+            .transpose()?
+            .unwrap_or(Chemistry::Auto),
+    })
+}
+
+/// Renders a MathML document into braille.
+///
+/// Returns an object with `unicode` and `dots` strings and `spans`, an array of
+/// `{start, end, sourceStart, sourceEnd}` objects mapping cell ranges to UTF-16 ranges of the input.
+/// When `options.brf` is set the result also contains a `brf` string. `options.decimalMark` is
+/// `"comma"` (default) or `"point"`, `options.chemistry` is `"off"`, `"auto"` (default) or `"on"`.
+#[wasm_bindgen]
+pub fn render(mathml: &str, options: JsValue) -> Result<Object, JsValue> {
+    let render_options = render_options(
+        get_string(&options, "decimalMark")?.as_deref(),
+        get_string(&options, "chemistry")?.as_deref(),
+    )
+    .map_err(|e| JsError::new(&e))?;
+    let (res, spans) = rbmath::render_with_spans_and_options(mathml, render_options)
+        .map_err(|e| JsError::new(&e.to_string()))?;
+    let utf16_offset = |byte: usize| mathml[..byte].encode_utf16().count() as u32;
+    let js_spans = Array::new();
+    for span in spans {
+        let s = Object::new();
+        set(&s, "start", span.cells.start as u32)?;
+        set(&s, "end", span.cells.end as u32)?;
+        set(&s, "sourceStart", utf16_offset(span.source.start))?;
+        set(&s, "sourceEnd", utf16_offset(span.source.end))?;
+        js_spans.push(&s);
+    }
+    let result = Object::new();
+    set(&result, "unicode", res.to_unicode())?;
+    set(&result, "dots", res.to_dots())?;
+    if get_flag(&options, "brf")? {
+        set(&result, "brf", res.to_brf())?;
+    }
+    set(&result, "spans", js_spans)?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use rbmath::DecimalMark;
+
+    use super::*;
+
+    #[test]
+    fn options() {
+        let options = render_options(None, None).unwrap();
+        assert_eq!(options.decimal_mark, DecimalMark::Comma);
+        assert_eq!(options.chemistry, Chemistry::Auto);
+        let options = render_options(Some("point"), Some("off")).unwrap();
+        assert_eq!(options.decimal_mark, DecimalMark::Point);
+        assert_eq!(options.chemistry, Chemistry::Off);
+        assert!(render_options(Some("dot"), None).is_err());
+        assert!(render_options(None, Some("yes")).is_err());
+    }
+}
@@ -0,0 +1,49 @@
+// Build the package first: wasm-pack build --target nodejs
+// Then run: node --test tests/
+import { test } from 'node:test'
+import assert from 'node:assert/strict'
+import { createRequire } from 'node:module'
+
+const require = createRequire(import.meta.url)
+const rbmath = require('../pkg/rbmath_wasm.js')
+const temml = require('../../convertml/node_modules/temml')
+
+test('renders MathML into unicode and dots', () => {
+    const res = rbmath.render('<math><mn>12</mn></math>')
+    assert.equal(res.unicode, '⠼⠁⠃')
+    assert.equal(res.dots, '3456 1 12')
+    assert.equal(res.brf, undefined)
+})
+
+test('renders BRF on request', () => {
+    const res = rbmath.render('<math><mi>x</mi></math>', { brf: true })
+    assert.equal(res.brf, ',X')
+})
+
+test('applies the rendering options', () => {
+    const number = '<math><mn>1,5</mn></math>'
+    assert.equal(rbmath.render(number).dots, '3456 1 2 15')
+    assert.equal(rbmath.render(number, { decimalMark: 'point' }).dots, '3456 1 3 15')
+    const salt = '<math><mi>N</mi><mi>a</mi><mi>C</mi><mi>l</mi></math>'
+    assert.equal(rbmath.render(salt, { chemistry: 'off' }).dots, '46 1345 6 1 46 14 6 123')
+    assert.equal(rbmath.render(salt, { chemistry: 'on' }).dots, '46 1345 1 46 14 123')
+    assert.throws(() => rbmath.render(number, { decimalMark: 'dot' }), /unknown decimal mark/)
+})
+
+test('maps spans to the source', () => {
+    const ml = '<math><mi>x</mi><mo>−</mo><mn>1</mn></math>'
+    const res = rbmath.render(ml)
+    const minus = res.spans.find(s => ml.slice(s.sourceStart, s.sourceEnd) === '<mo>−</mo>')
+    assert.ok(minus)
+    assert.equal(res.dots.split(' ').slice(minus.start, minus.end).join(' '), '0 36')
+})
+
+test('reports invalid MathML', () => {
+    assert.throws(() => rbmath.render('<math>'), /invalid XML/)
+    assert.throws(() => rbmath.render('<svg/>'), /unexpected root element/)
+})
+
+test('renders temml output', () => {
+    const ml = temml.renderToString('\\frac{1}{2}+\\sqrt{x}', { throwOnError: true })
+    assert.equal(rbmath.render(ml).dots, '3456 1 23 0 235 146 156 6 1346 1456')
+})